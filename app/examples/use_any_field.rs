@@ -0,0 +1,49 @@
+use derive::{add_greet, Greet, Greet2};
+use greet::Greet;
+
+add_greet!(
+    #[greet(content = "Dear {title} {name}, we will write to {email}.")]
+    struct Customer {
+        title: String,
+        name: String,
+        email: String,
+    }
+);
+
+#[derive(Greet)]
+#[greet(content = "{title} {name} reporting for duty.")]
+struct Officer {
+    title: String,
+    name: String,
+    badge: u32,
+}
+
+#[derive(Greet2)]
+#[greet2(content = "Badge {badge:>6}: {name}")]
+struct Guard {
+    name: String,
+    badge: u32,
+}
+
+fn main() {
+    let customer = Customer {
+        title: "Ms.".to_string(),
+        name: "Lan".to_string(),
+        email: "lan@example.com".to_string(),
+    };
+    customer.greet();
+
+    let officer = Officer {
+        title: "Lieutenant".to_string(),
+        name: "Minh".to_string(),
+        badge: 1042,
+    };
+    officer.greet();
+    println!("(badge #{})", officer.badge);
+
+    let guard = Guard {
+        name: "Tuan".to_string(),
+        badge: 7,
+    };
+    guard.greet();
+}
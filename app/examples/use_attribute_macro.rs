@@ -1,4 +1,5 @@
 use derive::greet;
+use greet::Greet;
 
 #[greet(content = "Hello, my name is {name}  and I a {age} years old.")]
 struct Person {
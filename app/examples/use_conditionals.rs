@@ -0,0 +1,72 @@
+use derive::{greet, Greet2};
+use greet::Greet;
+
+#[derive(Greet2)]
+#[greet2(
+    content = "{if visits > 0}Welcome back{else}Welcome{/if}, {name}! You are {if age >= 18}an adult{else}a minor{/if}.{if is_vip()} Enjoy the lounge.{/if}"
+)]
+struct Visitor {
+    name: String,
+    age: u8,
+    visits: u32,
+}
+
+impl Visitor {
+    fn is_vip(&self) -> bool {
+        self.visits >= 10
+    }
+}
+
+// Conditions can test `Option`s and nest inside other sections
+#[greet(
+    content = "Hello {name}.{?score} {if score >= 90}Great{else}Good{/if} job, you scored {score}.{/score}"
+)]
+struct Student {
+    name: String,
+    score: Option<u32>,
+}
+
+#[derive(Greet2)]
+enum Account {
+    #[greet2(
+        content = "{if items.is_empty()}Your cart is empty{else}You have {items.len()} item(s){/if}"
+    )]
+    Cart { items: Vec<String> },
+    #[greet2(content = "{if 0 == 1}unreachable{else}Signed out{/if}")]
+    Guest,
+}
+
+fn main() {
+    for visitor in [
+        Visitor {
+            name: "Hieu".to_string(),
+            age: 30,
+            visits: 12,
+        },
+        Visitor {
+            name: "Lan".to_string(),
+            age: 16,
+            visits: 0,
+        },
+    ] {
+        visitor.greet();
+    }
+
+    Student {
+        name: "Minh".to_string(),
+        score: Some(95),
+    }
+    .greet();
+    Student {
+        name: "An".to_string(),
+        score: None,
+    }
+    .greet();
+
+    Account::Cart {
+        items: vec!["book".to_string()],
+    }
+    .greet();
+    Account::Cart { items: Vec::new() }.greet();
+    Account::Guest.greet();
+}
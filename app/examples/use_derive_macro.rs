@@ -1,4 +1,5 @@
 use derive::Greet;
+use greet::Greet;
 
 #[derive(Greet)]
 struct PerSon {
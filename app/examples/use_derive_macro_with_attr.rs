@@ -1,4 +1,5 @@
 use derive::Greet2;
+use greet::Greet;
 
 #[derive(Greet2)]
 #[greet2(content = "Hello, my name is {name}  and I a {age} years old.")]
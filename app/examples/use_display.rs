@@ -0,0 +1,57 @@
+use derive::{add_greet, greet, Greet, Greet2};
+use greet::Greet;
+
+add_greet! {
+    #[greet(display)]
+    struct Student {
+        name: String,
+        age: u32,
+    }
+}
+
+#[greet(content = "Good morning, I am {title} {name}.", display)]
+struct Teacher {
+    title: String,
+    name: String,
+}
+
+#[derive(Greet)]
+#[greet(content = "{name} the robot, version {version}.", display)]
+struct Robot {
+    name: String,
+    version: f32,
+}
+
+#[derive(Greet2)]
+#[greet2(display)]
+enum Visitor {
+    #[greet2(content = "Welcome back, {0}!")]
+    Member(String),
+    Guest,
+}
+
+fn main() {
+    let student = Student {
+        name: "Hieu".to_string(),
+        age: 24,
+    };
+    let teacher = Teacher {
+        title: "Dr.".to_string(),
+        name: "Lan".to_string(),
+    };
+    let robot = Robot {
+        name: "Bender".to_string(),
+        version: 2.1,
+    };
+    let visitors = [Visitor::Member("Minh".to_string()), Visitor::Guest];
+
+    // `Display` writes exactly what `greet()` prints
+    assert_eq!(format!("{student}"), student.greeting());
+    println!("{student}");
+    println!("{teacher}");
+    println!("[{robot:^40}]");
+    for visitor in &visitors {
+        println!("{visitor}");
+        visitor.greet();
+    }
+}
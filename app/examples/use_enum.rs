@@ -0,0 +1,49 @@
+use derive::{greet, Greet2};
+use greet::Greet;
+
+#[derive(Greet2)]
+enum Visitor {
+    #[greet2(content = "Welcome back, {name}! Visit number {visits}.")]
+    Member {
+        name: String,
+        visits: u32,
+    },
+    #[greet2(content = "Hello, guest #{0} ({1}).")]
+    Guest(u32, String),
+    // Unit variants default to their name: "Good morning"
+    GoodMorning,
+}
+
+#[greet(content = "Status: {code}")]
+enum Status {
+    // Uses the enum-level template
+    Failed {
+        code: i32,
+    },
+    #[greet(content = "All good ({0}) after {1} retries")]
+    Ok(&'static str, u8),
+    #[greet(content = "Status unknown")]
+    Unknown,
+}
+
+fn main() {
+    let visitors = [
+        Visitor::Member {
+            name: "Hieu".to_string(),
+            visits: 3,
+        },
+        Visitor::Guest(42, "walk-in".to_string()),
+        Visitor::GoodMorning,
+    ];
+    for visitor in &visitors {
+        visitor.greet();
+    }
+
+    for status in [
+        Status::Failed { code: 2 },
+        Status::Ok("done", 1),
+        Status::Unknown,
+    ] {
+        status.greet();
+    }
+}
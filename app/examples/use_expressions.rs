@@ -0,0 +1,73 @@
+use derive::{greet, Greet2};
+use greet::Greet;
+
+const BIRTHDAY_BONUS: u32 = 1;
+
+#[derive(Greet2)]
+#[greet2(
+    content = "Hi, I am {full_name()}. Next year I will be {age + BIRTHDAY_BONUS} and I have {tags.len()} hobbies: {tags.join(\", \")}."
+)]
+struct Person {
+    first_name: String,
+    last_name: String,
+    age: u32,
+    tags: Vec<String>,
+}
+
+impl Person {
+    fn full_name(&self) -> String {
+        format!("{} {}", self.first_name, self.last_name)
+    }
+}
+
+#[greet(
+    content = "Room {number}: {if_empty(guests.is_empty())}, {guests.len() * 2} towels needed."
+)]
+struct Room {
+    number: u32,
+    guests: Vec<String>,
+}
+
+impl Room {
+    fn if_empty(&self, empty: bool) -> &'static str {
+        if empty {
+            "vacant"
+        } else {
+            "occupied"
+        }
+    }
+}
+
+#[derive(Greet2)]
+enum Reading {
+    #[greet2(content = "{celsius * 9.0 / 5.0 + 32.0:.1} °F")]
+    Celsius { celsius: f64 },
+    #[greet2(content = "{unit().to_uppercase()} {0}")]
+    Raw(i64),
+}
+
+impl Reading {
+    fn unit(&self) -> &'static str {
+        "raw"
+    }
+}
+
+fn main() {
+    let hieu = Person {
+        first_name: "Hieu".to_string(),
+        last_name: "Nguyen".to_string(),
+        age: 24,
+        tags: vec!["chess".to_string(), "rust".to_string()],
+    };
+    hieu.greet();
+
+    let room = Room {
+        number: 101,
+        guests: vec!["Lan".to_string(), "Minh".to_string()],
+    };
+    room.greet();
+
+    for reading in [Reading::Celsius { celsius: 21.5 }, Reading::Raw(42)] {
+        reading.greet();
+    }
+}
@@ -0,0 +1,58 @@
+use std::fmt;
+
+use derive::{greet, Greet2};
+use greet::Greet;
+
+mod fmt_helpers {
+    use std::fmt;
+
+    pub fn shout(value: &str, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}!", value.to_uppercase())
+    }
+}
+
+fn years(value: &u32, f: &mut fmt::Formatter) -> fmt::Result {
+    match value {
+        1 => write!(f, "1 year"),
+        n => write!(f, "{n} years"),
+    }
+}
+
+#[derive(Greet2)]
+#[greet2(content = "Hello, I am {full_name}, {age} old. Call me {nickname}.")]
+struct Person {
+    #[greet(rename = "full_name")]
+    name: String,
+    #[greet(with = years)]
+    age: u32,
+    #[greet(default = "whatever you like")]
+    nickname: Option<String>,
+    #[greet(skip)]
+    password: String,
+}
+
+#[greet(content = "{0} says {1}")]
+struct Shout(&'static str, #[greet(with = fmt_helpers::shout)] String);
+
+fn main() {
+    let people = [
+        Person {
+            name: "Hieu".to_string(),
+            age: 24,
+            nickname: None,
+            password: "hunter2".to_string(),
+        },
+        Person {
+            name: "Lan".to_string(),
+            age: 1,
+            nickname: Some("Lanny".to_string()),
+            password: "correct horse".to_string(),
+        },
+    ];
+    for person in &people {
+        person.greet();
+        println!("(password has {} characters)", person.password.len());
+    }
+
+    Shout("Minh", "hello".to_string()).greet();
+}
@@ -0,0 +1,56 @@
+use derive::{greet, Greet2};
+use greet::Greet;
+
+/// Filters of our own: functions taking the value as anything `Display`,
+/// then the filter's arguments.
+mod greet_filters {
+    use core::fmt::Display;
+
+    pub fn shout(value: impl Display) -> impl Display {
+        format!("{value}!")
+    }
+
+    pub fn wrap(value: impl Display, left: &str, right: &str) -> impl Display {
+        format!("{left}{value}{right}")
+    }
+}
+
+#[derive(Greet2)]
+#[greet2(
+    content = "Hi, I am {name|trim|title_case}, {role|upper}. {bio|truncate:24}",
+    filters = greet_filters
+)]
+#[greet2(
+    name = "cheer",
+    content = "{name|trim|lower|shout} {nickname|capitalize|wrap:\"(\",\")\"|\"\"}"
+)]
+struct Person {
+    name: String,
+    role: &'static str,
+    bio: String,
+    nickname: Option<String>,
+}
+
+// Filters work on any `Display` value, and before the format spec is applied
+#[greet(content = "[{code:>8|lower}] [{score|truncate:3}]")]
+struct Entry<T> {
+    code: &'static str,
+    score: T,
+}
+
+fn main() {
+    let person = Person {
+        name: "  hIEU nguyen ".to_string(),
+        role: "maintainer",
+        bio: "Writes procedural macros for fun and profit.".to_string(),
+        nickname: Some("hi".to_string()),
+    };
+    person.greet();
+    println!("{}", person.cheer_string());
+
+    Entry {
+        code: "ABC",
+        score: 12.345,
+    }
+    .greet();
+}
@@ -0,0 +1,46 @@
+use derive::{greet, Greet2};
+use greet::Greet;
+
+#[derive(Greet2)]
+#[greet2(
+    content = "[{name:<8}|{age:>3}|{score:8.2}|{id:#06x}|{flags:#b}|{ratio:+.1e}|{name:?}|{tags:?}]"
+)]
+struct Player {
+    name: String,
+    age: u32,
+    score: f64,
+    id: u32,
+    flags: u8,
+    ratio: f64,
+    tags: Vec<&'static str>,
+}
+
+// Widths and precisions can be read from `usize` fields
+#[greet(content = "{label:*^width$}|{value:.digits$}|{value:>width$.digits$}")]
+struct Gauge {
+    label: String,
+    value: f64,
+    width: usize,
+    digits: usize,
+}
+
+fn main() {
+    let player = Player {
+        name: "Hieu".to_string(),
+        age: 24,
+        score: 97.456,
+        id: 255,
+        flags: 5,
+        ratio: 1234.5,
+        tags: vec!["mvp", "rookie"],
+    };
+    player.greet();
+
+    let gauge = Gauge {
+        label: "cpu".to_string(),
+        value: 42.4242,
+        width: 11,
+        digits: 1,
+    };
+    gauge.greet();
+}
@@ -0,0 +1,66 @@
+use std::fmt::Debug;
+
+use derive::{add_greet, greet, Greet, Greet2};
+use greet::Greet;
+
+add_greet!(
+    #[greet(content = "{name} has {count} item(s).")]
+    struct Basket<'a, T>
+    where
+        T: Debug,
+    {
+        name: &'a str,
+        count: usize,
+        items: Vec<T>,
+    }
+);
+
+#[derive(Greet)]
+#[greet(content = "Hello, I am {name}.")]
+struct Person<'a> {
+    name: &'a str,
+}
+
+// `T` is used in the template and gets a `Display` bound; `U` is not and
+// stays unbounded.
+#[greet(content = "Tag {tag} (slot {slot}) of {size}.")]
+struct Tagged<T, U, const N: usize> {
+    tag: T,
+    slot: usize,
+    size: &'static str,
+    extra: [U; N],
+}
+
+#[derive(Greet2)]
+#[greet2(content = "Debugging {value:?}")]
+struct Inspect<V: Clone> {
+    value: V,
+}
+
+struct NotDisplay;
+
+fn main() {
+    let basket = Basket {
+        name: "Fruits",
+        count: 2,
+        items: vec!["apple", "pear"],
+    };
+    basket.greet();
+    println!("{:?}", basket.items);
+
+    Person { name: "Hieu" }.greet();
+
+    let tagged = Tagged {
+        tag: 'a',
+        slot: 3,
+        size: "small",
+        extra: [NotDisplay, NotDisplay],
+    };
+    tagged.greet();
+    println!("{} extra values", tagged.extra.len());
+
+    Inspect {
+        value: vec![1, 2, 3],
+    }
+    .greet();
+}
@@ -0,0 +1,60 @@
+use derive::{add_greet, greet, Greet, Greet2};
+use greet::Greet;
+
+add_greet!(
+    struct Student {
+        name: String,
+        age: u32,
+    }
+);
+
+#[derive(Greet)]
+struct Teacher {
+    name: String,
+    age: u32,
+}
+
+#[greet(content = "Hi, I'm {name}, {age} years young.")]
+struct Parent {
+    name: String,
+    age: u32,
+}
+
+#[derive(Greet2)]
+#[greet2(content = "Hey there, {name} here ({age}).")]
+struct Friend {
+    name: String,
+    age: u32,
+}
+
+fn welcome<T: Greet>(x: &T) {
+    println!("Welcome! {}", x.greeting());
+}
+
+fn main() {
+    let student = Student {
+        name: "Hieu".to_string(),
+        age: 24,
+    };
+    welcome(&student);
+
+    let greeters: Vec<Box<dyn Greet>> = vec![
+        Box::new(student),
+        Box::new(Teacher {
+            name: "Lan".to_string(),
+            age: 41,
+        }),
+        Box::new(Parent {
+            name: "Minh".to_string(),
+            age: 52,
+        }),
+        Box::new(Friend {
+            name: "Tuan".to_string(),
+            age: 25,
+        }),
+    ];
+
+    for greeter in &greeters {
+        greeter.greet();
+    }
+}
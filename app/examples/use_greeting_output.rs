@@ -0,0 +1,41 @@
+use std::fmt::Write as _;
+use std::io::Write as _;
+
+use derive::Greet2;
+use greet::Greet;
+
+#[derive(Greet2)]
+#[greet2(content = "Hello, my name is {name} and I am {age} years old.")]
+struct Person {
+    name: String,
+    age: u32,
+}
+
+fn main() -> std::io::Result<()> {
+    let hieu = Person {
+        name: "Hieu".to_string(),
+        age: 24,
+    };
+
+    // As a String, e.g. for a GUI label or a test assertion
+    let text = hieu.greeting();
+    assert_eq!(text, "Hello, my name is Hieu and I am 24 years old.");
+
+    // Into any `fmt::Write`, e.g. a buffer we are building anyway
+    let mut log_line = String::from("[greeting] ");
+    hieu.write_greeting(&mut log_line)
+        .expect("writing to a String");
+    writeln!(log_line).expect("writing to a String");
+    print!("{log_line}");
+
+    // Into any `io::Write`, e.g. stderr, a file or a socket
+    let mut bytes = Vec::new();
+    hieu.write_greeting_io(&mut bytes)?;
+    assert_eq!(bytes, text.as_bytes());
+    hieu.write_greeting_io(&mut std::io::stderr())?;
+    writeln!(std::io::stderr())?;
+
+    // Or simply print it
+    hieu.greet();
+    Ok(())
+}
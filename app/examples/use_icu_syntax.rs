@@ -0,0 +1,78 @@
+use derive::{greet, Greet2};
+use greet::Greet;
+
+#[derive(Greet2)]
+#[greet2(
+    syntax = "icu",
+    content = "I'm {name}, {age, plural, one {# year} other {# years}} old. {gender, select, female {She} male {He} other {They}} will be there."
+)]
+#[greet2(
+    name = "guests",
+    content = "{guests, plural, offset:1 =0 {Nobody is coming} =1 {Only {name} is coming} one {{name} and # other guest are coming} other {{name} and # other guests are coming}}."
+)]
+struct Person {
+    name: String,
+    age: u32,
+    gender: &'static str,
+    guests: usize,
+}
+
+// Apostrophes quote syntax characters, as in ICU
+#[greet(
+    syntax = "icu",
+    content = "Set '{braces}' to {n, number}, that''s {n, plural, =1 {one '#'} other {'#'#}}."
+)]
+struct Quoting {
+    n: i64,
+}
+
+/// Types used in `select` provide their selector through `AsRef<str>`.
+enum Plan {
+    Free,
+    Pro,
+}
+
+impl AsRef<str> for Plan {
+    fn as_ref(&self) -> &str {
+        match self {
+            Plan::Free => "free",
+            Plan::Pro => "pro",
+        }
+    }
+}
+
+#[derive(Greet2)]
+#[greet2(syntax = "icu")]
+enum Account {
+    #[greet2(content = "{0, select, pro {Thanks for supporting us!} other {Upgrade to Pro.}}")]
+    User(Plan),
+    Guest,
+}
+
+fn main() {
+    for (age, gender, guests) in [
+        (1, "female", 0),
+        (30, "male", 1),
+        (45, "other", 2),
+        (52, "female", 5),
+    ] {
+        let person = Person {
+            name: "Lan".to_string(),
+            age,
+            gender,
+            guests,
+        };
+        person.greet();
+        println!("{}", person.guests_string());
+    }
+    for n in [1, 3] {
+        Quoting { n }.greet();
+    }
+    for account in [
+        Account::User(Plan::Free),
+        Account::User(Plan::Pro),
+        Account::Guest,
+    ] {
+        account.greet();
+    }
+}
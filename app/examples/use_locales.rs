@@ -0,0 +1,83 @@
+use derive::Greet2;
+
+#[derive(Greet2)]
+#[greet2(
+    default_locale = "en",
+    content = "Hi, I'm {name}. I have {cats} {cats|plural:\"cat\",\"cats\"}."
+)]
+#[greet2(
+    locale = "vi",
+    content = "Xin chào, tôi là {name}. Tôi có {cats} con mèo."
+)]
+#[greet2(
+    locale = "fr",
+    content = "Bonjour, je suis {name}. J'ai {cats} {cats|plural:\"chat\",\"chats\"}."
+)]
+#[greet2(
+    locale = "ru",
+    content = "Привет, я {name}. У меня {cats} {cats|plural(one = \"кошка\", few = \"кошки\", many = \"кошек\", other = \"кошки\")}."
+)]
+#[greet2(
+    locale = "ja",
+    content = "こんにちは、{name}です。猫が{cats}匹います。"
+)]
+#[greet2(
+    locale = "pt-BR",
+    content = "Olá, eu sou {name}. Tenho {cats} {cats|plural:\"gato\",\"gatos\"}."
+)]
+// Without a chain of its own, `pt-PT` would fall back to `pt`, then to English
+#[greet2(fallback = "pt-PT > pt-BR")]
+struct Person {
+    name: String,
+    cats: u32,
+}
+
+// Variants are translated one by one, and fall back to the default locale
+#[derive(Greet2)]
+#[greet2(default_locale = "en")]
+#[greet2(name = "subject", content = "News from us")]
+enum Notice {
+    #[greet2(content = "Welcome back!")]
+    #[greet2(locale = "vi", content = "Chào mừng trở lại!")]
+    Welcome,
+    #[greet2(content = "You have {0} new {0|plural:\"message\",\"messages\"}.")]
+    #[greet2(
+        locale = "fr",
+        content = "Vous avez {0} {0|plural:\"nouveau message\",\"nouveaux messages\"}."
+    )]
+    Inbox(u32),
+    #[greet2(name = "subject", locale = "vi", content = "Hẹn gặp lại")]
+    SeeYouSoon,
+}
+
+fn main() {
+    for cats in [0, 1, 3, 5] {
+        let person = Person {
+            name: "Lan".to_string(),
+            cats,
+        };
+        for locale in ["en", "vi-VN", "fr_CA", "ru", "ja", "pt-PT", "de"] {
+            print!("{locale:>6}: ");
+            person.greet_in(locale);
+        }
+    }
+
+    let person = Person {
+        name: "Hieu".to_string(),
+        cats: 2,
+    };
+    println!("{}", person.greeting_in("fr-FR"));
+
+    for notice in [
+        Notice::Welcome,
+        Notice::Inbox(1),
+        Notice::Inbox(0),
+        Notice::SeeYouSoon,
+    ] {
+        for locale in ["en", "vi", "fr"] {
+            print!("{locale}: ");
+            notice.greet_in(locale);
+        }
+        println!("subject: {}", notice.subject_string_in("vi"));
+    }
+}
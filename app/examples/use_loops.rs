@@ -0,0 +1,66 @@
+use derive::{greet, Greet2};
+use greet::Greet;
+
+#[derive(Greet2)]
+#[greet2(
+    content = "I am {name}, I like {#each hobbies list}{it}{/each}. Tags: {#each tags sep=\" \"}#{it}{/each}"
+)]
+struct Person {
+    name: String,
+    hobbies: Vec<String>,
+    tags: &'static [&'static str],
+}
+
+struct Friend {
+    name: String,
+    since: u32,
+}
+
+// Items can be named, and collections can be any expression
+#[greet(
+    content = "{team}: {#each member in members sep=\", \" last=\" or \"}{member.name} ({member.since}){/each}. Best scores: {#each scores.iter().rev() sep=\" > \"}{it}{/each}"
+)]
+struct Team {
+    team: &'static str,
+    members: Vec<Friend>,
+    scores: [u32; 3],
+}
+
+#[derive(Greet2)]
+enum Order {
+    #[greet2(content = "Order of {#each items list}{?it}{it}{/it}{/each}")]
+    Items { items: Vec<Option<String>> },
+    #[greet2(content = "Nothing ordered{#each 0}{it}{/each}")]
+    Empty(Vec<u8>),
+}
+
+fn main() {
+    Person {
+        name: "Hieu".to_string(),
+        hobbies: vec!["chess".to_string(), "rust".to_string(), "tea".to_string()],
+        tags: &["dev", "oss"],
+    }
+    .greet();
+
+    Team {
+        team: "Blue",
+        members: vec![
+            Friend {
+                name: "Lan".to_string(),
+                since: 2019,
+            },
+            Friend {
+                name: "Minh".to_string(),
+                since: 2021,
+            },
+        ],
+        scores: [7, 12, 30],
+    }
+    .greet();
+
+    Order::Items {
+        items: vec![Some("tea".to_string()), Some("cake".to_string())],
+    }
+    .greet();
+    Order::Empty(Vec::new()).greet();
+}
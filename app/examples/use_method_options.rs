@@ -0,0 +1,46 @@
+mod school {
+    use derive::{greet, Greet2};
+
+    #[greet(
+        content = "Good morning, I am {title} {name}.",
+        method = "introduce",
+        vis = "pub(crate)"
+    )]
+    pub struct Teacher {
+        pub title: String,
+        pub name: String,
+    }
+
+    // Named greetings default to the type's own visibility
+    #[derive(Greet2)]
+    #[greet2(content = "Hi, I am {name}.")]
+    #[greet2(name = "farewell", content = "See you tomorrow, {name}!")]
+    #[greet2(
+        name = "excuse",
+        content = "Sorry, {name} forgot the homework.",
+        method = "apologize",
+        vis = "pub(crate)"
+    )]
+    pub struct Student {
+        pub name: String,
+    }
+}
+
+use greet::Greet;
+use school::{Student, Teacher};
+
+fn main() {
+    let teacher = Teacher {
+        title: "Dr.".to_string(),
+        name: "Lan".to_string(),
+    };
+    teacher.introduce();
+    assert_eq!(teacher.introduce_string(), teacher.greeting());
+
+    let student = Student {
+        name: "Hieu".to_string(),
+    };
+    student.greet();
+    student.apologize();
+    student.farewell();
+}
@@ -0,0 +1,51 @@
+use derive::Greet2;
+use greet::Greet;
+
+#[derive(Greet2)]
+#[greet2(content = "Welcome, {name}!")]
+#[greet2(name = "farewell", content = "Bye {name}, see you in {days} days.")]
+#[greet2(
+    name = "reminder",
+    content = "{name}, your table is booked for {guests}.",
+    display
+)]
+pub struct Guest {
+    name: String,
+    days: u32,
+    guests: u32,
+}
+
+#[derive(Greet2)]
+#[greet2(name = "farewell", content = "Goodbye!")]
+enum Visitor {
+    #[greet2(content = "Welcome back, {0}!")]
+    #[greet2(name = "farewell", content = "See you soon, {0}!")]
+    Member(String),
+    Guest,
+}
+
+fn main() {
+    let guest = Guest {
+        name: "Hieu".to_string(),
+        days: 3,
+        guests: 2,
+    };
+
+    // The unnamed greeting implements `Greet`
+    guest.greet();
+
+    // Each named greeting gets its own methods
+    guest.farewell();
+    assert_eq!(guest.farewell_string(), "Bye Hieu, see you in 3 days.");
+    let mut log = String::new();
+    guest.write_reminder(&mut log).expect("writing to a String");
+    println!("{log}");
+
+    // `display` picked the reminder
+    assert_eq!(guest.to_string(), log);
+
+    for visitor in [Visitor::Member("Minh".to_string()), Visitor::Guest] {
+        visitor.greet();
+        visitor.farewell();
+    }
+}
@@ -0,0 +1,70 @@
+use derive::{greet, Greet2};
+use greet::Greet;
+
+struct Address {
+    city: String,
+    country: Country,
+}
+
+struct Country {
+    name: String,
+}
+
+#[derive(Greet2)]
+#[greet2(content = "Hello, I am {self.name} from {address.city}, {address.country.name}.")]
+struct Person {
+    name: String,
+    address: Address,
+}
+
+#[greet(content = "Parcel for {0.city} ({1.name}).")]
+struct Parcel(Address, Country);
+
+#[derive(Greet2)]
+enum Traveller {
+    #[greet2(content = "{name} is flying from {from.city} to {to.city}.")]
+    Flying {
+        name: String,
+        from: Address,
+        to: Address,
+    },
+    #[greet2(content = "Welcome home, {0}!")]
+    Home(String),
+}
+
+fn address(city: &str, country: &str) -> Address {
+    Address {
+        city: city.to_string(),
+        country: Country {
+            name: country.to_string(),
+        },
+    }
+}
+
+fn main() {
+    let hieu = Person {
+        name: "Hieu".to_string(),
+        address: address("Hanoi", "Vietnam"),
+    };
+    hieu.greet();
+
+    let parcel = Parcel(
+        address("Da Nang", "Vietnam"),
+        Country {
+            name: "Vietnam".to_string(),
+        },
+    );
+    parcel.greet();
+
+    let travellers = [
+        Traveller::Flying {
+            name: "Lan".to_string(),
+            from: address("Hue", "Vietnam"),
+            to: address("Paris", "France"),
+        },
+        Traveller::Home("Minh".to_string()),
+    ];
+    for traveller in &travellers {
+        traveller.greet();
+    }
+}
@@ -0,0 +1,72 @@
+use derive::{greet, Greet2};
+use greet::Greet;
+
+#[derive(Greet2)]
+#[greet2(
+    content = "Hi, I am {name}{?nickname} (aka {nickname}){/nickname}. Call me {nickname|\"friend\"}.{?phone} My number is {phone}.{/phone}"
+)]
+struct Person {
+    name: String,
+    nickname: Option<String>,
+    phone: Option<u64>,
+}
+
+// A plain `{field}` on an `Option` writes nothing when it is `None`
+#[greet(content = "Order #{id}{?note}: {note}{/note} {coupon}")]
+struct Order<C> {
+    id: u32,
+    note: Option<String>,
+    coupon: Option<C>,
+}
+
+#[derive(Greet2)]
+enum Contact {
+    #[greet2(content = "Email {address}{?name} ({name}){/name}")]
+    Email {
+        address: String,
+        name: Option<String>,
+    },
+    #[greet2(content = "Phone {0|\"unknown\"}")]
+    Phone(Option<String>),
+}
+
+fn main() {
+    let people = [
+        Person {
+            name: "Hieu".to_string(),
+            nickname: Some("Hi".to_string()),
+            phone: Some(123456),
+        },
+        Person {
+            name: "Lan".to_string(),
+            nickname: None,
+            phone: None,
+        },
+    ];
+    for person in &people {
+        person.greet();
+    }
+
+    let order = Order {
+        id: 7,
+        note: Some("leave at the door".to_string()),
+        coupon: Some("SPRING10"),
+    };
+    order.greet();
+    let order = Order::<&str> {
+        id: 8,
+        note: None,
+        coupon: None,
+    };
+    order.greet();
+
+    for contact in [
+        Contact::Email {
+            address: "lan@example.com".to_string(),
+            name: Some("Lan".to_string()),
+        },
+        Contact::Phone(None),
+    ] {
+        contact.greet();
+    }
+}
@@ -0,0 +1,88 @@
+use derive::{greet, Greet2};
+use greet::plural::Category;
+use greet::Greet;
+
+#[derive(Greet2)]
+#[greet2(
+    content = "I am {name}, {age} {age|plural:\"year\",\"years\"} old, with {pets} {pets|plural(one = \"pet\", other = \"pets\")}."
+)]
+struct Person {
+    name: String,
+    age: u8,
+    pets: i32,
+}
+
+// Other languages plug in their own rules
+#[greet(
+    content = "J'ai {age} {age|plural:\"an\",\"ans\"}.",
+    plural_rules = greet::plural::french
+)]
+struct Personne {
+    age: u32,
+}
+
+#[greet(
+    content = "Mam {files} {files|plural(one = \"plik\", few = \"pliki\", many = \"plików\", other = \"pliku\")}.",
+    plural_rules = greet::plural::polish
+)]
+struct Folder {
+    files: u32,
+}
+
+// A count of a generic type only needs to be a `greet::plural::Count`
+#[greet(content = "{n} {n|plural:\"item\",\"items\"} in the cart")]
+struct Cart<T> {
+    n: T,
+}
+
+/// Welsh-like rules, to show every category.
+fn counting(n: u64) -> Category {
+    match n {
+        0 => Category::Zero,
+        1 => Category::One,
+        2 => Category::Two,
+        _ => Category::Other,
+    }
+}
+
+#[derive(Greet2)]
+#[greet2(
+    content = "{len|plural(zero = \"no\", one = \"a single\", two = \"a pair of\", other = \"many\")} {len|plural:\"apple\",\"apples\"}",
+    plural_rules = counting
+)]
+// Filters apply to the value inside an `Option`, before the fallback
+#[greet2(
+    name = "stock",
+    content = "{left|\"no\"} {left|plural:\"apple\",\"apples\"|\"apples\"} left"
+)]
+struct Basket {
+    len: usize,
+    left: Option<u64>,
+}
+
+fn main() {
+    for (age, pets) in [(1, 1), (30, 0), (45, -2)] {
+        Person {
+            name: "Hieu".to_string(),
+            age,
+            pets,
+        }
+        .greet();
+    }
+    for age in [0, 1, 2] {
+        Personne { age }.greet();
+    }
+    for files in [1, 3, 5, 22] {
+        Folder { files }.greet();
+    }
+    Cart { n: 1u16 }.greet();
+    Cart { n: -3i64 }.greet();
+    for len in 0..4 {
+        let basket = Basket {
+            len,
+            left: (len != 1).then_some(len as u64 / 2),
+        };
+        basket.greet();
+        println!("{}", basket.stock_string());
+    }
+}
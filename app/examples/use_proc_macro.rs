@@ -1,4 +1,5 @@
 use derive::add_greet;
+use greet::Greet;
 
 add_greet!(
     struct Person {
@@ -0,0 +1,30 @@
+use greet::Greet;
+
+mod people {
+    use derive::add_greet;
+
+    add_greet!(
+        /// A person who can introduce themselves.
+        #[derive(Debug, Clone, PartialEq)]
+        #[repr(C)]
+        #[greet(content = "Hello, my name is {name} and I am {age} years old.")]
+        pub struct Person<T>
+        where
+            T: Copy,
+        {
+            pub name: String,
+            pub age: T,
+        }
+    );
+}
+
+fn main() {
+    let hieu = people::Person {
+        name: "Hieu".to_string(),
+        age: 24u32,
+    };
+    let twin = hieu.clone();
+
+    hieu.greet();
+    println!("{:?} == {:?}: {}", hieu, twin, hieu == twin);
+}
@@ -0,0 +1,46 @@
+use derive::Greet2;
+use greet::Greet;
+
+// Templates live in app/greetings/person.toml; editing it rebuilds this
+// example
+#[derive(Greet2)]
+#[greet2(path = "greetings/person.toml", key = "welcome")]
+#[greet2(locale = "vi", key = "vi.welcome")]
+#[greet2(name = "farewell", key = "farewell")]
+struct Person {
+    name: String,
+    years: u32,
+    days: u32,
+}
+
+// Variants read their own keys from the type's file, and can still mix in
+// templates written in place
+#[derive(Greet2)]
+#[greet2(path = "greetings/person.toml")]
+enum Notice {
+    #[greet2(key = "notice.welcome")]
+    Welcome(String),
+    #[greet2(key = "notice.inbox")]
+    Inbox { count: usize },
+    #[greet2(content = "Nothing new.")]
+    Quiet,
+}
+
+fn main() {
+    let person = Person {
+        name: "Hieu".to_string(),
+        years: 3,
+        days: 1,
+    };
+    person.greet();
+    person.greet_in("vi");
+    person.farewell();
+
+    for notice in [
+        Notice::Welcome("Lan".to_string()),
+        Notice::Inbox { count: 2 },
+        Notice::Quiet,
+    ] {
+        notice.greet();
+    }
+}
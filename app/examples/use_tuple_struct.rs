@@ -0,0 +1,34 @@
+use derive::{add_greet, greet, Greet, Greet2};
+use greet::Greet;
+
+add_greet!(
+    #[greet(content = "User #{0}")]
+    #[derive(Debug, Clone, Copy)]
+    pub struct UserId(u64);
+);
+
+#[derive(Greet)]
+#[greet(content = "{1}, {0} years old")]
+struct Age(u32, &'static str);
+
+#[greet(content = "Point at ({0}, {1:.1})")]
+struct Point(i32, f64);
+
+#[derive(Greet2)]
+#[greet2(content = "Nothing to see here.")]
+struct Empty;
+
+// Unit structs without a template are greeted with their name.
+#[derive(Greet)]
+struct HelloWorld;
+
+fn main() {
+    let id = UserId(7);
+    id.greet();
+    println!("{:?}", id);
+
+    Age(24, "Hieu").greet();
+    Point(3, 4.25).greet();
+    Empty.greet();
+    HelloWorld.greet();
+}
@@ -0,0 +1,1050 @@
+//! Extracts the greeting templates of a crate for translators.
+//!
+//! Walks Rust sources with `syn`, finds every `#[greet(...)]` and
+//! `#[greet2(...)]` with a `content`, on types and variants alike, and every
+//! `add_greet!` invocation, and writes a catalog of the templates with their
+//! placeholders, type and location, as JSON and as a gettext `.pot` file.
+//! Greetings without a `content` are listed with the template the macros
+//! give them: [`DEFAULT_CONTENT`] for the structs with named fields of
+//! `add_greet!` and `#[derive(Greet)]`, and the name of unit structs and
+//! variants, e.g. `Good morning` for `GoodMorning`.
+//!
+//! ```text
+//! cargo run --bin greet-extract -- [--json FILE] [--pot FILE] [PATH...]
+//! ```
+//!
+//! Paths are files or directories, `src` by default. Without `--json` or
+//! `--pot`, the JSON catalog is written to stdout; `-` as a file also means
+//! stdout. Templates read from a file with `key` are left out, as they are
+//! already outside the code.
+
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use proc_macro2::Span;
+use syn::ext::IdentExt;
+use syn::punctuated::Punctuated;
+use syn::visit::{self, Visit};
+use syn::{Attribute, DeriveInput, Expr, Fields, Ident, Lit, LitStr, Pat, Token, Variant};
+use template::{
+    humanize, member_name, plain_name, Argument, Segment, Syntax, Template, DEFAULT_CONTENT,
+};
+
+/// One template found in the sources.
+#[derive(Default)]
+struct Entry {
+    /// The macro or attribute it was given to: `greet`, `greet2` or
+    /// `add_greet!`.
+    source: &'static str,
+    type_name: String,
+    variant: Option<String>,
+    /// The name of a named greeting.
+    greeting: Option<String>,
+    locale: Option<String>,
+    icu: bool,
+    template: String,
+    placeholders: Vec<String>,
+    file: String,
+    line: usize,
+    column: usize,
+}
+
+impl Entry {
+    fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "macro": self.source,
+            "type": self.type_name,
+            "variant": self.variant,
+            "greeting": self.greeting,
+            "locale": self.locale,
+            "syntax": if self.icu { "icu" } else { "default" },
+            "template": self.template,
+            "placeholders": self.placeholders,
+            "file": self.file,
+            "line": self.line,
+            "column": self.column,
+        })
+    }
+
+    /// The gettext context telling apart equal templates of different
+    /// greetings: `Person`, `Person.farewell` or `Notice::Welcome`.
+    fn context(&self) -> String {
+        let mut context = self.type_name.clone();
+        if let Some(variant) = &self.variant {
+            write!(context, "::{variant}").unwrap();
+        }
+        if let Some(greeting) = &self.greeting {
+            write!(context, ".{greeting}").unwrap();
+        }
+        context
+    }
+}
+
+/// The string options of one `#[greet(...)]` or `#[greet2(...)]`.
+struct Options {
+    attr: &'static str,
+    values: Vec<(String, String, Span)>,
+}
+
+impl Options {
+    /// Reads `attr` if it is one of ours. Options with other values than
+    /// string literals, such as `filters = path`, and flags are skipped.
+    fn parse(attr: &Attribute) -> Option<syn::Result<Self>> {
+        let name = attr.path().segments.last()?.ident.to_string();
+        let attr_name = match name.as_str() {
+            "greet" => "greet",
+            "greet2" => "greet2",
+            _ => return None,
+        };
+        let mut values = Vec::new();
+        if let syn::Meta::Path(_) = attr.meta {
+            // `#[greet]`, the attribute macro without options
+            return Some(Ok(Self {
+                attr: attr_name,
+                values,
+            }));
+        }
+        let parsed = attr.parse_nested_meta(|meta| {
+            let key = meta.path.require_ident()?.to_string();
+            if meta.input.peek(Token![=]) {
+                if let Expr::Lit(syn::ExprLit {
+                    lit: Lit::Str(lit), ..
+                }) = meta.value()?.parse::<Expr>()?
+                {
+                    values.push((key, lit.value(), lit.span()));
+                }
+            }
+            Ok(())
+        });
+        Some(parsed.map(|()| Self {
+            attr: attr_name,
+            values,
+        }))
+    }
+
+    fn get(&self, key: &str) -> Option<&str> {
+        self.values
+            .iter()
+            .find(|(other, _, _)| other == key)
+            .map(|(_, value, _)| value.as_str())
+    }
+
+    fn content(&self) -> Option<(&str, Span)> {
+        self.values
+            .iter()
+            .find(|(key, _, _)| key == "content")
+            .map(|(_, value, span)| (value.as_str(), *span))
+    }
+
+    /// Whether these options give greeting `name` a template of the default
+    /// locale, in the code or in a template file.
+    fn gives(&self, attr: &str, name: Option<&str>) -> bool {
+        self.attr == attr
+            && self.get("name") == name
+            && self.get("locale").is_none()
+            && (self.get("content").is_some() || self.get("key").is_some())
+    }
+}
+
+/// The macro implementing `Greet` for a type, which decides the templates
+/// of the greetings without a `content`.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Macro {
+    /// `add_greet!` or `#[derive(Greet)]`, which greet structs with named
+    /// fields with [`DEFAULT_CONTENT`].
+    Greet,
+    /// The `#[greet]` attribute macro.
+    Attribute,
+    Greet2,
+}
+
+impl Macro {
+    /// The macro of an item with `attrs`, if any.
+    fn of(attrs: &[Attribute], in_macro: bool) -> Option<Self> {
+        if in_macro {
+            return Some(Self::Greet);
+        }
+        let mut derived = None;
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident("derive")) {
+            let Ok(paths) =
+                attr.parse_args_with(Punctuated::<syn::Path, Token![,]>::parse_terminated)
+            else {
+                continue;
+            };
+            for path in paths {
+                match path.segments.last().map(|s| s.ident.to_string()).as_deref() {
+                    Some("Greet2") => return Some(Self::Greet2),
+                    Some("Greet") => derived = Some(Self::Greet),
+                    _ => {}
+                }
+            }
+        }
+        derived.or_else(|| {
+            attrs
+                .iter()
+                .any(|attr| {
+                    attr.path()
+                        .segments
+                        .last()
+                        .is_some_and(|s| s.ident == "greet")
+                })
+                .then_some(Self::Attribute)
+        })
+    }
+
+    /// The attribute holding the options of the type's greetings.
+    fn attr(self) -> &'static str {
+        match self {
+            Self::Greet | Self::Attribute => "greet",
+            Self::Greet2 => "greet2",
+        }
+    }
+}
+
+/// Collects the entries of one file.
+struct Extractor<'a> {
+    file: &'a str,
+    entries: Vec<Entry>,
+    problems: Vec<String>,
+}
+
+impl Extractor<'_> {
+    fn options(&mut self, attrs: &[Attribute]) -> Vec<Options> {
+        let mut options = Vec::new();
+        for attr in attrs {
+            match Options::parse(attr) {
+                Some(Ok(parsed)) => options.push(parsed),
+                Some(Err(error)) => self.problem(error.span(), &error.to_string()),
+                None => {}
+            }
+        }
+        options
+    }
+
+    fn problem(&mut self, span: Span, message: &str) {
+        let start = span.start();
+        self.problems.push(format!(
+            "{}:{}:{}: {message}",
+            self.file,
+            start.line,
+            start.column + 1
+        ));
+    }
+
+    /// Records the templates of a type and of its variants, `fields` being
+    /// those of a struct.
+    fn item<'v>(
+        &mut self,
+        ident: &Ident,
+        attrs: &[Attribute],
+        fields: Option<&Fields>,
+        variants: impl IntoIterator<Item = &'v Variant>,
+        in_macro: bool,
+    ) {
+        let options = self.options(attrs);
+        // `syntax` applies to every template of the type
+        let icu = options.iter().any(|o| o.get("syntax") == Some("icu"));
+        let type_name = ident.to_string();
+        self.record(&options, &type_name, None, icu, in_macro);
+        let mut variant_options = Vec::new();
+        for variant in variants {
+            let options = self.options(&variant.attrs);
+            let name = variant.ident.to_string();
+            self.record(&options, &type_name, Some(name), icu, in_macro);
+            variant_options.push((variant, options));
+        }
+
+        let Some(kind) = Macro::of(attrs, in_macro) else {
+            return;
+        };
+        let attr = kind.attr();
+        let source = if in_macro { "add_greet!" } else { attr };
+        // The greetings of the type, named by any of its attributes
+        let mut greetings = Vec::new();
+        for options in options
+            .iter()
+            .chain(variant_options.iter().flat_map(|(_, options)| options))
+            .filter(|options| options.attr == attr)
+        {
+            let name = options.get("name");
+            if !greetings.contains(&name) {
+                greetings.push(name);
+            }
+        }
+        if greetings.is_empty() {
+            greetings.push(None);
+        }
+        for name in greetings {
+            if options.iter().any(|options| options.gives(attr, name)) {
+                continue;
+            }
+            let implicit = |variant: Option<&Ident>, template: String| Entry {
+                source,
+                type_name: type_name.clone(),
+                variant: variant.map(Ident::to_string),
+                greeting: name.map(str::to_string),
+                icu,
+                template,
+                ..Entry::default()
+            };
+            if let Some(fields) = fields {
+                let template = match fields {
+                    Fields::Named(_) if kind == Macro::Greet => DEFAULT_CONTENT.to_string(),
+                    _ if fields.is_empty() => humanize(&type_name),
+                    _ => continue,
+                };
+                self.push(implicit(None, template), ident.span());
+            }
+            for (variant, options) in &variant_options {
+                if variant.fields.is_empty() && !options.iter().any(|o| o.gives(attr, name)) {
+                    let template = humanize(&variant.ident.to_string());
+                    let entry = implicit(Some(&variant.ident), template);
+                    self.push(entry, variant.ident.span());
+                }
+            }
+        }
+    }
+
+    /// Records the templates given with `content`.
+    fn record(
+        &mut self,
+        options: &[Options],
+        type_name: &str,
+        variant: Option<String>,
+        icu: bool,
+        in_macro: bool,
+    ) {
+        for options in options {
+            let Some((template, span)) = options.content() else {
+                continue;
+            };
+            let entry = Entry {
+                source: if in_macro { "add_greet!" } else { options.attr },
+                type_name: type_name.to_string(),
+                variant: variant.clone(),
+                greeting: options.get("name").map(str::to_string),
+                locale: options.get("locale").map(str::to_string),
+                icu,
+                template: template.to_string(),
+                ..Entry::default()
+            };
+            self.push(entry, span);
+        }
+    }
+
+    /// Adds `entry`, written at `span`, with the placeholders of its
+    /// template.
+    fn push(&mut self, mut entry: Entry, span: Span) {
+        let syntax = if entry.icu {
+            Syntax::Icu
+        } else {
+            Syntax::Default
+        };
+        match placeholders(&entry.template, syntax) {
+            Ok(placeholders) => entry.placeholders = placeholders,
+            Err(errors) => {
+                for error in errors {
+                    self.problem(span, &error.to_string());
+                }
+                return;
+            }
+        }
+        let start = span.start();
+        entry.file = self.file.to_string();
+        entry.line = start.line;
+        entry.column = start.column + 1;
+        self.entries.push(entry);
+    }
+}
+
+impl<'ast> Visit<'ast> for Extractor<'_> {
+    fn visit_item_struct(&mut self, item: &'ast syn::ItemStruct) {
+        self.item(&item.ident, &item.attrs, Some(&item.fields), [], false);
+        visit::visit_item_struct(self, item);
+    }
+
+    fn visit_item_enum(&mut self, item: &'ast syn::ItemEnum) {
+        self.item(&item.ident, &item.attrs, None, &item.variants, false);
+        visit::visit_item_enum(self, item);
+    }
+
+    fn visit_macro(&mut self, mac: &'ast syn::Macro) {
+        if mac
+            .path
+            .segments
+            .last()
+            .is_some_and(|s| s.ident == "add_greet")
+        {
+            match mac.parse_body::<DeriveInput>() {
+                Ok(input) => {
+                    let (fields, variants) = match &input.data {
+                        syn::Data::Struct(data) => (Some(&data.fields), Vec::new()),
+                        syn::Data::Enum(data) => (None, data.variants.iter().collect()),
+                        syn::Data::Union(_) => (None, Vec::new()),
+                    };
+                    self.item(&input.ident, &input.attrs, fields, variants, true);
+                }
+                Err(error) => self.problem(error.span(), &error.to_string()),
+            }
+        }
+        visit::visit_macro(self, mac);
+    }
+}
+
+/// The fields and expressions a template reads, in order of first use, read
+/// with the parser of the macros. The items of loops are left out.
+fn placeholders(template: &str, syntax: Syntax) -> darling::Result<Vec<String>> {
+    let mut errors = darling::Error::accumulator();
+    let parsed = Template::parse(
+        &LitStr::new(template, Span::call_site()),
+        syntax,
+        &mut errors,
+    );
+    errors.finish()?;
+    let mut reads = Reads::default();
+    reads.segments(&parsed.segments);
+    Ok(reads.names)
+}
+
+/// Collects what the segments of a template read.
+#[derive(Default)]
+struct Reads {
+    names: Vec<String>,
+    /// The items of the loops around the current segment.
+    items: Vec<String>,
+    /// The names bound within the condition being visited.
+    locals: Vec<String>,
+}
+
+impl Reads {
+    fn segments(&mut self, segments: &[Segment]) {
+        for segment in segments {
+            match segment {
+                Segment::Text(_) | Segment::PluralCount(_) => {}
+                Segment::Placeholder(placeholder) => {
+                    self.argument(&placeholder.arg);
+                    if let Some(spec) = &placeholder.spec {
+                        let (width, precision) = spec.count_fields();
+                        for member in width.into_iter().chain(precision) {
+                            self.push(member_name(member));
+                        }
+                    }
+                }
+                Segment::IfSome { member, body, .. } => {
+                    self.push(member_name(member));
+                    self.segments(body);
+                }
+                Segment::If {
+                    cond,
+                    then,
+                    otherwise,
+                    ..
+                } => {
+                    self.visit_expr(cond);
+                    self.segments(then);
+                    self.segments(otherwise);
+                }
+                Segment::Each { each, body, .. } => {
+                    self.argument(&each.collection);
+                    self.items.push(each.item.clone());
+                    self.segments(body);
+                    self.items.pop();
+                }
+                Segment::Plural {
+                    count,
+                    exact,
+                    forms,
+                    other,
+                    ..
+                } => {
+                    self.argument(count);
+                    for (_, body) in exact {
+                        self.segments(body);
+                    }
+                    for (_, body) in forms {
+                        self.segments(body);
+                    }
+                    self.segments(other);
+                }
+                Segment::Select {
+                    value,
+                    cases,
+                    other,
+                    ..
+                } => {
+                    self.argument(value);
+                    for (_, body) in cases {
+                        self.segments(body);
+                    }
+                    self.segments(other);
+                }
+            }
+        }
+    }
+
+    /// A field path such as `address.city`, or an expression as
+    /// `prettyplease` writes it.
+    fn argument(&mut self, arg: &Argument) {
+        match arg {
+            Argument::Field { member, path } => {
+                let names = std::iter::once(member).chain(path).map(member_name);
+                self.push(names.collect::<Vec<_>>().join("."));
+            }
+            Argument::Expr(expr) => {
+                let file = syn::parse_quote!(const _: () = #expr;);
+                let written = prettyplease::unparse(&file);
+                let expr = written
+                    .trim_end()
+                    .trim_start_matches("const _: () = ")
+                    .trim_end_matches(';');
+                self.push(expr.to_string());
+            }
+        }
+    }
+
+    /// Adds `name` unless it is already there or starts with a loop's item.
+    fn push(&mut self, name: String) {
+        let root = name
+            .split(|c: char| !c.is_alphanumeric() && c != '_')
+            .next();
+        if !self.items.iter().any(|item| Some(item.as_str()) == root) && !self.names.contains(&name)
+        {
+            self.names.push(name);
+        }
+    }
+
+    /// Visits with the names `pats` bind in scope.
+    fn with_locals<'p>(
+        &mut self,
+        pats: impl IntoIterator<Item = &'p Pat>,
+        visit: impl FnOnce(&mut Self),
+    ) {
+        let outer = self.locals.len();
+        for pat in pats {
+            PatBindings(&mut self.locals).visit_pat(pat);
+        }
+        visit(self);
+        self.locals.truncate(outer);
+    }
+}
+
+/// The names a condition reads. Names bound within it are left out, as are
+/// capitalized names such as `None` or `MAX`, which are constructors and
+/// constants, and the plain functions it calls, which are methods.
+impl<'ast> Visit<'ast> for Reads {
+    fn visit_expr_path(&mut self, path: &'ast syn::ExprPath) {
+        if let Some(ident) = path.path.get_ident() {
+            let name = ident.unraw().to_string();
+            if !name.starts_with(char::is_uppercase) && !self.locals.contains(&name) {
+                self.push(name);
+            }
+        }
+    }
+
+    fn visit_expr_call(&mut self, call: &'ast syn::ExprCall) {
+        if plain_name(&call.func).is_none() {
+            self.visit_expr(&call.func);
+        }
+        for arg in &call.args {
+            self.visit_expr(arg);
+        }
+    }
+
+    // Patterns are not visited: they bind names rather than read them.
+    fn visit_expr_closure(&mut self, closure: &'ast syn::ExprClosure) {
+        self.with_locals(&closure.inputs, |reads| reads.visit_expr(&closure.body));
+    }
+
+    fn visit_arm(&mut self, arm: &'ast syn::Arm) {
+        self.with_locals([&arm.pat], |reads| {
+            if let Some((_, guard)) = &arm.guard {
+                reads.visit_expr(guard);
+            }
+            reads.visit_expr(&arm.body);
+        });
+    }
+
+    // `if let` binds names for its `then` branch only.
+    fn visit_expr_if(&mut self, e: &'ast syn::ExprIf) {
+        let pat = match &*e.cond {
+            Expr::Let(cond) => {
+                self.visit_expr(&cond.expr);
+                Some(&*cond.pat)
+            }
+            cond => {
+                self.visit_expr(cond);
+                None
+            }
+        };
+        self.with_locals(pat, |reads| reads.visit_block(&e.then_branch));
+        if let Some((_, otherwise)) = &e.else_branch {
+            self.visit_expr(otherwise);
+        }
+    }
+
+    fn visit_expr_let(&mut self, e: &'ast syn::ExprLet) {
+        self.visit_expr(&e.expr);
+    }
+
+    fn visit_block(&mut self, block: &'ast syn::Block) {
+        let outer = self.locals.len();
+        for stmt in &block.stmts {
+            match stmt {
+                syn::Stmt::Local(local) => {
+                    if let Some(init) = &local.init {
+                        self.visit_expr(&init.expr);
+                        if let Some((_, diverge)) = &init.diverge {
+                            self.visit_expr(diverge);
+                        }
+                    }
+                    PatBindings(&mut self.locals).visit_pat(&local.pat);
+                }
+                stmt => self.visit_stmt(stmt),
+            }
+        }
+        self.locals.truncate(outer);
+    }
+}
+
+/// Collects the names a pattern binds.
+struct PatBindings<'n>(&'n mut Vec<String>);
+
+impl<'ast> Visit<'ast> for PatBindings<'_> {
+    fn visit_pat_ident(&mut self, pat: &'ast syn::PatIdent) {
+        self.0.push(pat.ident.unraw().to_string());
+        visit::visit_pat_ident(self, pat);
+    }
+}
+
+/// The `.pot` file of the templates without a locale, which are the source
+/// strings translators start from. Like xgettext, the same template under
+/// the same context is one message listing every place it is used in, as
+/// `msgfmt` rejects duplicate messages.
+fn pot(entries: &[Entry]) -> String {
+    let mut messages: Vec<(String, &str, Vec<&Entry>)> = Vec::new();
+    for entry in entries.iter().filter(|entry| entry.locale.is_none()) {
+        let context = entry.context();
+        match messages
+            .iter_mut()
+            .find(|(c, template, _)| *c == context && *template == entry.template)
+        {
+            Some((_, _, uses)) => uses.push(entry),
+            None => messages.push((context, &entry.template, vec![entry])),
+        }
+    }
+
+    let mut out =
+        String::from("msgid \"\"\nmsgstr \"\"\n\"Content-Type: text/plain; charset=UTF-8\\n\"\n");
+    for (context, template, uses) in messages {
+        out.push('\n');
+        if !uses[0].placeholders.is_empty() {
+            writeln!(out, "#. placeholders: {}", uses[0].placeholders.join(", ")).unwrap();
+        }
+        if uses.iter().any(|entry| entry.icu) {
+            out.push_str("#. syntax: icu\n");
+        }
+        out.push_str("#:");
+        for entry in &uses {
+            write!(out, " {}:{}", entry.file, entry.line).unwrap();
+        }
+        out.push('\n');
+        writeln!(out, "msgctxt {}", pot_string(&context)).unwrap();
+        writeln!(out, "msgid {}", pot_string(template)).unwrap();
+        out.push_str("msgstr \"\"\n");
+    }
+    out
+}
+
+/// `s` quoted for a `.pot` file, one quoted line per line of `s`.
+fn pot_string(s: &str) -> String {
+    let escape = |line: &str| {
+        let mut escaped = String::from("\"");
+        for c in line.chars() {
+            match c {
+                '"' => escaped.push_str("\\\""),
+                '\\' => escaped.push_str("\\\\"),
+                '\n' => escaped.push_str("\\n"),
+                '\t' => escaped.push_str("\\t"),
+                c => escaped.push(c),
+            }
+        }
+        escaped.push('"');
+        escaped
+    };
+    if !s.trim_end_matches('\n').contains('\n') {
+        return escape(s);
+    }
+    let lines = s.split_inclusive('\n').map(escape).collect::<Vec<_>>();
+    format!("\"\"\n{}", lines.join("\n"))
+}
+
+/// The `.rs` files under `path`, sorted, skipping `target` and hidden
+/// directories.
+fn rust_files(path: &Path, files: &mut Vec<PathBuf>) -> std::io::Result<()> {
+    if path.is_file() {
+        files.push(path.to_path_buf());
+        return Ok(());
+    }
+    let mut entries = std::fs::read_dir(path)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<std::io::Result<Vec<_>>>()?;
+    entries.sort();
+    for entry in entries {
+        let name = entry.file_name().unwrap_or_default().to_string_lossy();
+        if entry.is_dir() && name != "target" && !name.starts_with('.') {
+            rust_files(&entry, files)?;
+        } else if entry.extension().is_some_and(|ext| ext == "rs") {
+            files.push(entry);
+        }
+    }
+    Ok(())
+}
+
+fn write_output(path: &str, contents: &str) -> std::io::Result<()> {
+    if path == "-" {
+        print!("{contents}");
+        Ok(())
+    } else {
+        std::fs::write(path, contents)
+    }
+}
+
+const USAGE: &str = "usage: greet-extract [--json FILE] [--pot FILE] [PATH...]";
+
+fn main() -> ExitCode {
+    let mut json = None;
+    let mut pot_path = None;
+    let mut paths = Vec::new();
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--json" | "--pot" => {
+                let Some(value) = args.next() else {
+                    eprintln!("{arg} needs a file\n{USAGE}");
+                    return ExitCode::FAILURE;
+                };
+                if arg == "--json" {
+                    json = Some(value);
+                } else {
+                    pot_path = Some(value);
+                }
+            }
+            "-h" | "--help" => {
+                println!("{USAGE}");
+                return ExitCode::SUCCESS;
+            }
+            _ if arg.starts_with("--") => {
+                eprintln!("unknown option `{arg}`\n{USAGE}");
+                return ExitCode::FAILURE;
+            }
+            _ => paths.push(PathBuf::from(arg)),
+        }
+    }
+    if paths.is_empty() {
+        paths.push(PathBuf::from("src"));
+    }
+    if json.is_none() && pot_path.is_none() {
+        json = Some("-".to_string());
+    }
+
+    let mut files = Vec::new();
+    for path in &paths {
+        if let Err(error) = rust_files(path, &mut files) {
+            eprintln!("cannot read {}: {error}", path.display());
+            return ExitCode::FAILURE;
+        }
+    }
+    let mut entries = Vec::new();
+    let mut failed = false;
+    for file in &files {
+        let name = file.display().to_string();
+        let parsed = std::fs::read_to_string(file)
+            .map_err(|error| error.to_string())
+            .and_then(|source| syn::parse_file(&source).map_err(|error| error.to_string()));
+        let ast = match parsed {
+            Ok(ast) => ast,
+            Err(error) => {
+                eprintln!("{name}: {error}");
+                failed = true;
+                continue;
+            }
+        };
+        let mut extractor = Extractor {
+            file: &name,
+            entries: Vec::new(),
+            problems: Vec::new(),
+        };
+        extractor.visit_file(&ast);
+        for problem in &extractor.problems {
+            eprintln!("{problem}");
+        }
+        failed |= !extractor.problems.is_empty();
+        entries.extend(extractor.entries);
+    }
+
+    let outputs = [
+        json.map(|path| {
+            let catalog = entries.iter().map(Entry::to_json).collect::<Vec<_>>();
+            let json = serde_json::to_string_pretty(&catalog).expect("JSON values serialize");
+            (path, json + "\n")
+        }),
+        pot_path.map(|path| (path, pot(&entries))),
+    ];
+    for (path, contents) in outputs.into_iter().flatten() {
+        if let Err(error) = write_output(&path, &contents) {
+            eprintln!("cannot write {path}: {error}");
+            return ExitCode::FAILURE;
+        }
+    }
+    if failed {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(template: &str, syntax: Syntax) -> Vec<String> {
+        placeholders(template, syntax).unwrap_or_else(|e| panic!("{template}: {e}"))
+    }
+
+    /// The entries of the types in `src`.
+    fn extract(src: &str) -> Vec<Entry> {
+        let mut extractor = Extractor {
+            file: "src/lib.rs",
+            entries: Vec::new(),
+            problems: Vec::new(),
+        };
+        extractor.visit_file(&syn::parse_file(src).unwrap());
+        assert_eq!(extractor.problems, Vec::<String>::new());
+        extractor.entries
+    }
+
+    #[test]
+    fn placeholders_of_fields() {
+        assert_eq!(
+            names("{name} is {age:>3}, {name}.", Syntax::Default),
+            ["name", "age"]
+        );
+        assert_eq!(
+            names(
+                "{address.city}, {self.address.country} {0}",
+                Syntax::Default
+            ),
+            ["address.city", "address.country", "0"]
+        );
+        assert_eq!(
+            names("{label:*^width$}|{value:.digits$}", Syntax::Default),
+            ["label", "width", "value", "digits"]
+        );
+        assert_eq!(
+            names("{{literal}} {nickname|upper|\"friend\"}", Syntax::Default),
+            ["nickname"]
+        );
+    }
+
+    #[test]
+    fn placeholders_of_expressions() {
+        assert_eq!(
+            names(
+                "{age+1} {full_name()} {scores.iter().sum::<u32>()}",
+                Syntax::Default
+            ),
+            ["age + 1", "full_name()", "scores.iter().sum::<u32>()"]
+        );
+    }
+
+    #[test]
+    fn placeholders_of_sections() {
+        assert_eq!(
+            names(
+                "{?nickname}aka {nickname}{/nickname}{if age >= adult && !banned}ok{else}{reason}{/if}",
+                Syntax::Default
+            ),
+            ["nickname", "age", "adult", "banned", "reason"]
+        );
+        // Neither bindings, constants, constructors nor functions are fields
+        assert_eq!(
+            names(
+                "{if nick == None && tags.iter().any(|t| t.is_empty()) && age > MAX}!{/if}",
+                Syntax::Default
+            ),
+            ["nick", "tags", "age"]
+        );
+        assert_eq!(
+            names(
+                "{if pairs.iter().all(|&(a, b)| a < b + gap) || is_vip(id)}!{/if}",
+                Syntax::Default
+            ),
+            ["pairs", "gap", "id"]
+        );
+        // Loop items are not fields
+        assert_eq!(
+            names(
+                "{#each tag in tags sep=\", \"}{tag.name} of {owner}{/each}{#each scores.iter().rev()}{it:>3}{/each}",
+                Syntax::Default
+            ),
+            ["tags", "owner", "scores.iter().rev()"]
+        );
+    }
+
+    #[test]
+    fn placeholders_of_icu_templates() {
+        assert_eq!(
+            names(
+                "{name} has {count, plural, offset:1 =0 {no friends} one {# friend} other {{best} and # others}}, '{literal}'",
+                Syntax::Icu
+            ),
+            ["name", "count", "best"]
+        );
+        assert_eq!(
+            names(
+                "{gender, select, female {her {pronoun}} other {their}} {age, number}",
+                Syntax::Icu
+            ),
+            ["gender", "pronoun", "age"]
+        );
+    }
+
+    #[test]
+    fn placeholders_of_invalid_templates() {
+        assert!(placeholders("Hello, {name", Syntax::Default).is_err());
+        assert!(placeholders("{?nickname}unclosed", Syntax::Default).is_err());
+        assert!(placeholders("{count, plural, one {#}}", Syntax::Icu).is_err());
+    }
+
+    #[test]
+    fn implicit_templates() {
+        let entries = extract(
+            r#"
+            add_greet!(struct Person { name: String, age: u32 });
+
+            #[derive(Greet2)]
+            #[greet2(name = "farewell", content = "Bye!")]
+            enum Visitor {
+                #[greet2(content = "Welcome back, {0}!")]
+                Member(String),
+                GoodMorning,
+            }
+
+            #[greet]
+            struct HelloWorld;
+
+            #[derive(Debug)]
+            struct Unrelated;
+            "#,
+        );
+        let found = entries
+            .iter()
+            .map(|e| (e.source, e.context(), e.template.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            found,
+            [
+                (
+                    "add_greet!",
+                    "Person".to_string(),
+                    "Hello, my name is {name} and I am {age} years old."
+                ),
+                ("greet2", "Visitor.farewell".to_string(), "Bye!"),
+                (
+                    "greet2",
+                    "Visitor::Member".to_string(),
+                    "Welcome back, {0}!"
+                ),
+                ("greet2", "Visitor::GoodMorning".to_string(), "Good morning"),
+                ("greet", "HelloWorld".to_string(), "Hello world"),
+            ]
+        );
+        assert_eq!(entries[0].placeholders, ["name", "age"]);
+    }
+
+    #[test]
+    fn pot_strings() {
+        assert_eq!(pot_string("Hi {name}!"), r#""Hi {name}!""#);
+        assert_eq!(
+            pot_string("say \"hi\"\t\\ back\n"),
+            r#""say \"hi\"\t\\ back\n""#
+        );
+        assert_eq!(
+            pot_string("Dear {name},\nwelcome.\n"),
+            "\"\"\n\"Dear {name},\\n\"\n\"welcome.\\n\""
+        );
+    }
+
+    #[test]
+    fn pot_file() {
+        let entries = [
+            Entry {
+                source: "greet2",
+                type_name: "Notice".to_string(),
+                variant: Some("Inbox".to_string()),
+                greeting: Some("subject".to_string()),
+                template: "{0} new".to_string(),
+                placeholders: vec!["0".to_string()],
+                file: "src/lib.rs".to_string(),
+                line: 7,
+                ..Entry::default()
+            },
+            Entry {
+                source: "greet2",
+                type_name: "Notice".to_string(),
+                locale: Some("vi".to_string()),
+                template: "Xin chào".to_string(),
+                ..Entry::default()
+            },
+            Entry {
+                source: "greet2",
+                type_name: "Account".to_string(),
+                icu: true,
+                template: "Hello".to_string(),
+                file: "src/main.rs".to_string(),
+                line: 12,
+                ..Entry::default()
+            },
+            // The same message elsewhere is listed under the first one
+            Entry {
+                source: "greet",
+                type_name: "Account".to_string(),
+                template: "Hello".to_string(),
+                file: "src/admin.rs".to_string(),
+                line: 3,
+                ..Entry::default()
+            },
+            Entry {
+                source: "greet2",
+                type_name: "Notice".to_string(),
+                variant: Some("Inbox".to_string()),
+                greeting: Some("subject".to_string()),
+                template: "{0} new".to_string(),
+                placeholders: vec!["0".to_string()],
+                file: "src/lib.rs".to_string(),
+                line: 9,
+                ..Entry::default()
+            },
+        ];
+        assert_eq!(
+            pot(&entries),
+            r#"msgid ""
+msgstr ""
+"Content-Type: text/plain; charset=UTF-8\n"
+
+#. placeholders: 0
+#: src/lib.rs:7 src/lib.rs:9
+msgctxt "Notice::Inbox.subject"
+msgid "{0} new"
+msgstr ""
+
+#. syntax: icu
+#: src/main.rs:12 src/admin.rs:3
+msgctxt "Account"
+msgid "Hello"
+msgstr ""
+"#
+        );
+    }
+}
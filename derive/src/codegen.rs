@@ -0,0 +1,744 @@
+//! Code generation shared by all greet macros.
+
+use darling::{ast, Error};
+use proc_macro2::{Ident, Span, TokenStream, TokenTree};
+use quote::{format_ident, quote, ToTokens};
+use syn::{DeriveInput, Generics, LitStr, Path, Type, Visibility, WherePredicate};
+use template::{humanize, Syntax, Template};
+
+use crate::lower::{Case, Lowered};
+use crate::source::{annotate, Source};
+use crate::FieldArgs;
+
+/// An enum variant and its greeting templates, whichever attribute set them.
+pub(crate) struct Variant {
+    pub(crate) ident: Ident,
+    pub(crate) fields: ast::Fields<FieldArgs>,
+    /// The variant's own templates, keyed by the name of their greeting and
+    /// the locale of their translation.
+    pub(crate) contents: Vec<(Option<Ident>, Option<LitStr>, Content)>,
+}
+
+impl Variant {
+    fn content(&self, greeting: &Greeting) -> Option<&Content> {
+        self.contents
+            .iter()
+            .find(|(name, locale, _)| {
+                *name == greeting.name && same_locale(locale.as_ref(), greeting.locale.as_ref())
+            })
+            .map(|(_, _, content)| content)
+    }
+}
+
+/// A template as written in an attribute, or read from a file.
+#[derive(Clone, Debug)]
+pub(crate) struct Content {
+    pub(crate) lit: LitStr,
+    /// Where the template is, if it was read from a file.
+    pub(crate) source: Option<Source>,
+}
+
+impl Content {
+    /// Parses the template, saying where it is in errors if it was read from
+    /// a file.
+    fn parse(&self, syntax: Syntax, errors: &mut darling::error::Accumulator) -> Template {
+        let mut problems = Error::accumulator();
+        let template = Template::parse(&self.lit, syntax, &mut problems);
+        if let Err(error) = problems.finish() {
+            errors.push(annotate(self.source.as_ref(), error));
+        }
+        template
+    }
+
+    /// A case greeted with this template.
+    fn case<'a>(
+        &self,
+        variant: Option<&'a Ident>,
+        fields: &'a ast::Fields<FieldArgs>,
+        syntax: Syntax,
+        inherited: bool,
+        errors: &mut darling::error::Accumulator,
+    ) -> Case<'a> {
+        let template = self.parse(syntax, errors);
+        Case::new(variant, fields, template, inherited, self.lit.span())
+            .with_source(self.source.clone())
+    }
+}
+
+impl From<LitStr> for Content {
+    fn from(lit: LitStr) -> Self {
+        Self { lit, source: None }
+    }
+}
+
+impl ToTokens for Content {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        self.lit.to_tokens(tokens);
+    }
+}
+
+/// One greeting of a type.
+#[derive(Debug)]
+pub(crate) struct Greeting {
+    /// `None` for the primary greeting, which implements `Greet`. Named
+    /// greetings get inherent methods instead: `farewell()`,
+    /// `farewell_string()` and `write_farewell(w)` for `farewell`.
+    pub(crate) name: Option<Ident>,
+    /// The type-level template.
+    pub(crate) content: Option<Content>,
+    /// Whether the type implements `Display` with this greeting.
+    pub(crate) display: bool,
+    /// Overrides the name of the inherent methods. The unnamed greeting only
+    /// gets inherent methods, forwarding to `Greet`, when it is set.
+    pub(crate) method: Option<Ident>,
+    /// The visibility of the inherent methods, the type's own by default.
+    pub(crate) vis: Option<Visibility>,
+    /// The `fn(u64) -> greet::plural::Category` choosing plural forms,
+    /// `greet::plural::english` by default.
+    pub(crate) plural_rules: Option<Path>,
+    /// The syntax of the greeting's templates, `"default"` or `"icu"`.
+    pub(crate) syntax: Option<LitStr>,
+    /// A module of filters for placeholders, besides `greet::filters`.
+    pub(crate) filters: Option<Path>,
+    /// The locale of a translation, `None` for the greeting itself.
+    pub(crate) locale: Option<LitStr>,
+    /// The locale of the templates without `locale`. It picks their plural
+    /// rules, and ends every fallback chain.
+    pub(crate) default_locale: Option<LitStr>,
+    /// The greeting in other locales. Their `content` and `plural_rules` are
+    /// their own, and their other options those of the greeting.
+    pub(crate) translations: Vec<Greeting>,
+    /// Chains of locales to try instead of a locale and its parents, the
+    /// locale first.
+    pub(crate) fallbacks: Vec<Vec<String>>,
+}
+
+impl Greeting {
+    /// A greeting without template or options.
+    pub(crate) fn new(name: Option<Ident>) -> Self {
+        Self {
+            name,
+            content: None,
+            display: false,
+            method: None,
+            vis: None,
+            plural_rules: None,
+            syntax: None,
+            filters: None,
+            locale: None,
+            default_locale: None,
+            translations: Vec::new(),
+            fallbacks: Vec::new(),
+        }
+    }
+
+    /// The translation of the greeting into `locale`, added if it is new.
+    pub(crate) fn translation(&mut self, locale: LitStr) -> &mut Greeting {
+        let index = match self
+            .translations
+            .iter()
+            .position(|t| same_locale(t.locale.as_ref(), Some(&locale)))
+        {
+            Some(index) => index,
+            None => {
+                self.translations.push(Greeting {
+                    locale: Some(locale),
+                    ..Greeting::new(self.name.clone())
+                });
+                self.translations.len() - 1
+            }
+        };
+        &mut self.translations[index]
+    }
+
+    /// The name the inherent methods are derived from, if there are any.
+    fn method(&self) -> Option<&Ident> {
+        self.method.as_ref().or(self.name.as_ref())
+    }
+
+    /// The inherent methods generated for this greeting.
+    fn inherent_methods(&self) -> Vec<String> {
+        let mut methods = match self.method() {
+            Some(method) => vec![
+                method.to_string(),
+                format!("{method}_string"),
+                format!("write_{method}"),
+            ],
+            None => Vec::new(),
+        };
+        methods.extend(self.localized_methods().into_iter().flatten());
+        methods
+    }
+
+    /// The methods taking a locale, generated for translated greetings:
+    /// `greet_in`, `greeting_in` and `write_greeting_in` for the unnamed
+    /// greeting, and `farewell_in`, `farewell_string_in` and
+    /// `write_farewell_in` for `farewell`.
+    fn localized_methods(&self) -> Option<[String; 3]> {
+        if self.translations.is_empty() {
+            return None;
+        }
+        Some(match (&self.name, self.method()) {
+            (Some(_), Some(method)) => [
+                format!("{method}_in"),
+                format!("{method}_string_in"),
+                format!("write_{method}_in"),
+            ],
+            _ => [
+                "greet_in".to_string(),
+                "greeting_in".to_string(),
+                "write_greeting_in".to_string(),
+            ],
+        })
+    }
+}
+
+/// Locales compare ignoring case, with `_` and `-` interchangeable.
+pub(crate) fn locale_key(locale: &str) -> String {
+    locale.to_ascii_lowercase().replace('_', "-")
+}
+
+/// Whether `a` and `b` are the same locale, or both absent.
+pub(crate) fn same_locale(a: Option<&LitStr>, b: Option<&LitStr>) -> bool {
+    a.map(|a| locale_key(&a.value())) == b.map(|b| locale_key(&b.value()))
+}
+
+/// Describes the greeting called `name` for error messages.
+pub(crate) fn describe(name: &Option<Ident>) -> String {
+    match name {
+        Some(name) => format!("greeting `{name}`"),
+        None => "the unnamed greeting".to_string(),
+    }
+}
+
+/// Checks that no two greetings generate methods of the same name, nor
+/// methods that shadow those of `Greet`, and that `vis` is only given where
+/// there are inherent methods for it to apply to.
+fn check_methods(greetings: &[Greeting], errors: &mut darling::error::Accumulator) {
+    let mut taken: Vec<(String, String)> = Vec::new();
+    if greetings.iter().any(|greeting| greeting.name.is_none()) {
+        taken.extend(
+            ["greet", "greeting", "write_greeting", "write_greeting_io"]
+                .map(|method| (method.to_string(), "the `Greet` implementation".to_string())),
+        );
+    }
+    for greeting in greetings {
+        let methods = greeting.inherent_methods();
+        if let (true, Some(vis)) = (methods.is_empty(), &greeting.vis) {
+            errors.push(
+                Error::custom(
+                    "`vis` only applies to inherent methods: set `method` to generate them",
+                )
+                .with_span(vis),
+            );
+        }
+        if let Some((method, owner)) = methods
+            .iter()
+            .find_map(|method| taken.iter().find(|(other, _)| other == method))
+        {
+            errors.push(
+                Error::custom(format!(
+                    "`{method}` is already generated for {owner}: choose another `method`"
+                ))
+                .with_span(&greeting.method()),
+            );
+            continue;
+        }
+        let owner = describe(&greeting.name);
+        taken.extend(methods.into_iter().map(|method| (method, owner.clone())));
+    }
+}
+
+/// Generates the implementations of `greetings` for `input`, whose parsed
+/// fields and variants are `data`.
+///
+/// A struct is greeted with the greeting's `content`, or with its name if it
+/// is a unit struct without one. Each enum variant is greeted with its own
+/// template, falling back to the greeting's `content` and then, for unit
+/// variants, to the variant name.
+///
+/// Translations borrow the greeting's template for any case they have none
+/// for, and must use the same fields as it in the others.
+///
+/// Fails if a template is missing or refers to fields that do not exist or
+/// are skipped.
+pub(crate) fn impl_greet(
+    input: &DeriveInput,
+    greetings: &[Greeting],
+    data: &ast::Data<Variant, FieldArgs>,
+) -> darling::Result<TokenStream> {
+    let mut errors = Error::accumulator();
+    check_methods(greetings, &mut errors);
+    let cases = greetings
+        .iter()
+        .map(|greeting| {
+            let own = cases(input, greeting, None, data, &mut errors)
+                .into_iter()
+                .flatten()
+                .collect::<Vec<_>>();
+            let lowered = own
+                .iter()
+                .map(|case| case.lower(greeting, &mut errors))
+                .collect::<Vec<_>>();
+            let translations = greeting
+                .translations
+                .iter()
+                .map(|translation| {
+                    let translated = cases(input, translation, Some(greeting), data, &mut errors);
+                    let lowered = translated
+                        .iter()
+                        .map(|case| Some(case.as_ref()?.lower(translation, &mut errors)))
+                        .collect::<Vec<_>>();
+                    (translated, lowered)
+                })
+                .collect::<Vec<_>>();
+            (own, lowered, translations)
+        })
+        .collect::<Vec<_>>();
+    errors.finish()?;
+    let mut errors = Error::accumulator();
+    for (greeting, (cases, lowered, translations)) in greetings.iter().zip(&cases) {
+        for (translation, (translated, translated_lowered)) in
+            greeting.translations.iter().zip(translations)
+        {
+            let pairs = cases.iter().zip(lowered);
+            let translated_pairs = translated.iter().zip(translated_lowered);
+            for ((case, lowered), translated) in pairs.zip(translated_pairs) {
+                if let (Some(translated), Some(translated_lowered)) = translated {
+                    check_fields(
+                        greeting,
+                        translation,
+                        (case, lowered),
+                        (translated, translated_lowered),
+                        &mut errors,
+                    );
+                }
+            }
+        }
+    }
+    errors.finish()?;
+
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    // An enum without variants has no value to greet, and any code after
+    // matching on it would be unreachable.
+    let uninhabited = matches!(data, ast::Data::Enum(variants) if variants.is_empty());
+    // The body of a function writing the cases to `f`
+    let write = |cases: &[(&Case, &Lowered)]| match data {
+        _ if uninhabited => quote!(match *self {}),
+        ast::Data::Enum(_) => {
+            let arms = cases.iter().map(|(case, lowered)| case.match_arm(lowered));
+            quote! {
+                match self {
+                    #(#arms)*
+                }
+                ::core::result::Result::Ok(())
+            }
+        }
+        ast::Data::Struct(_) => {
+            let writes = cases.iter().map(|(_, lowered)| &lowered.writes);
+            quote! {
+                #(#writes)*
+                ::core::result::Result::Ok(())
+            }
+        }
+    };
+    let mut trait_impls = Vec::new();
+    let mut methods = Vec::new();
+    for (greeting, (cases, lowered, translations)) in greetings.iter().zip(&cases) {
+        let body = write(&cases.iter().zip(lowered).collect::<Vec<_>>());
+        let mut generics = input.generics.clone();
+        let bounds = format_bounds(&generics, lowered.iter());
+        generics
+            .make_where_clause()
+            .predicates
+            .extend(bounds.clone());
+        let (_, _, bounded_where_clause) = generics.split_for_impl();
+
+        let write_fn = greeting
+            .method()
+            .map(|method| format_ident!("write_{}", method));
+        let greeter = match &greeting.name {
+            None => {
+                trait_impls.push(quote! {
+                    impl #impl_generics ::greet::Greet for #ident #ty_generics #bounded_where_clause {
+                        fn write_greeting(&self, f: &mut dyn ::core::fmt::Write) -> ::core::fmt::Result {
+                            #body
+                        }
+                    }
+                });
+                quote!(self)
+            }
+            Some(_) => quote!(&::greet::from_fn(|f| self.#write_fn(f))),
+        };
+        if let (Some(method), Some(write_fn)) = (greeting.method(), &write_fn) {
+            let vis = greeting.vis.as_ref().unwrap_or(&input.vis);
+            let string = format_ident!("{}_string", method);
+            let where_bounds = (!bounds.is_empty()).then(|| quote!(where #(#bounds,)*));
+            let write_body = match &greeting.name {
+                None => quote!(::greet::Greet::write_greeting(self, f)),
+                Some(_) => body.clone(),
+            };
+            methods.push(quote! {
+                /// Writes the greeting into `f`.
+                #vis fn #write_fn(&self, f: &mut dyn ::core::fmt::Write) -> ::core::fmt::Result #where_bounds {
+                    #write_body
+                }
+
+                /// Returns the greeting as a `String`.
+                #vis fn #string(&self) -> ::std::string::String #where_bounds {
+                    ::greet::Greet::greeting(&::greet::from_fn(|f| self.#write_fn(f)))
+                }
+
+                /// Prints the greeting to stdout, followed by a newline.
+                #vis fn #method(&self) #where_bounds {
+                    ::greet::Greet::greet(&::greet::from_fn(|f| self.#write_fn(f)))
+                }
+            });
+        }
+        if let Some([print_in, string_in, write_in]) = greeting.localized_methods() {
+            let [print_in, string_in, write_in] =
+                [print_in, string_in, write_in].map(|name| format_ident!("{}", name));
+            let vis = greeting.vis.as_ref().unwrap_or(&input.vis);
+            // A case a translation has no template for is written as in the
+            // greeting's own templates
+            let translated = translations.iter().map(|(translated, translated_lowered)| {
+                translated
+                    .iter()
+                    .zip(translated_lowered)
+                    .zip(cases.iter().zip(lowered))
+                    .map(|((case, lowered), default)| match (case, lowered) {
+                        (Some(case), Some(lowered)) => (case, lowered),
+                        _ => default,
+                    })
+                    .collect::<Vec<_>>()
+            });
+            let locales = greeting.translations.iter().map(|t| &t.locale);
+            let bodies = translated.clone().map(|cases: Vec<_>| write(&cases));
+            let bounds = format_bounds(
+                &input.generics,
+                lowered
+                    .iter()
+                    .chain(translated.flatten().map(|(_, lowered)| lowered)),
+            );
+            let where_bounds = (!bounds.is_empty()).then(|| quote!(where #(#bounds,)*));
+            let chains = greeting
+                .fallbacks
+                .iter()
+                .map(|chain| quote!(&[#(#chain),*]));
+            let default_locale = greeting.default_locale.iter();
+            let write_default = match &write_fn {
+                Some(write_fn) if greeting.name.is_some() => quote!(self.#write_fn(f)),
+                _ => quote!(::greet::Greet::write_greeting(self, f)),
+            };
+            let write_in_body = if uninhabited {
+                quote!(match *self {})
+            } else {
+                quote! {
+                    for __greet_locale in ::greet::locale::fallbacks(locale, &[#(#chains),*]) {
+                        #(
+                            if ::greet::locale::matches(__greet_locale, #locales) {
+                                return { #bodies };
+                            }
+                        )*
+                        #(
+                            if ::greet::locale::matches(__greet_locale, #default_locale) {
+                                break;
+                            }
+                        )*
+                    }
+                    #write_default
+                }
+            };
+            methods.push(quote! {
+                /// Writes the greeting in `locale` into `f`, or in the first
+                /// locale it falls back to that it is translated into.
+                #vis fn #write_in(&self, locale: &str, f: &mut dyn ::core::fmt::Write) -> ::core::fmt::Result #where_bounds {
+                    #write_in_body
+                }
+
+                /// Returns the greeting in `locale` as a `String`.
+                #vis fn #string_in(&self, locale: &str) -> ::std::string::String #where_bounds {
+                    ::greet::Greet::greeting(&::greet::from_fn(|f| self.#write_in(locale, f)))
+                }
+
+                /// Prints the greeting in `locale` to stdout, followed by a
+                /// newline.
+                #vis fn #print_in(&self, locale: &str) #where_bounds {
+                    ::greet::Greet::greet(&::greet::from_fn(|f| self.#write_in(locale, f)))
+                }
+            });
+        }
+        if greeting.display {
+            trait_impls.push(quote! {
+                impl #impl_generics ::core::fmt::Display for #ident #ty_generics #bounded_where_clause {
+                    fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                        ::greet::fmt_greeting(#greeter, f)
+                    }
+                }
+            });
+        }
+    }
+
+    let inherent_impl = (!methods.is_empty()).then(|| {
+        quote! {
+            impl #impl_generics #ident #ty_generics #where_clause {
+                #(#methods)*
+            }
+        }
+    });
+    Ok(quote! {
+        #(#trait_impls)*
+        #inherent_impl
+    })
+}
+
+/// The cases `greeting` greets, pushing an error for every variant or struct
+/// it has no template for.
+///
+/// A translation of `fallback` has `None` for the cases it has no template
+/// of its own for, which `fallback` greets instead. Its other problems are
+/// those of `fallback` and are only reported once, for `fallback`.
+fn cases<'a>(
+    input: &DeriveInput,
+    greeting: &Greeting,
+    fallback: Option<&Greeting>,
+    data: &'a ast::Data<Variant, FieldArgs>,
+    errors: &mut darling::error::Accumulator,
+) -> Vec<Option<Case<'a>>> {
+    let syntax = match greeting.syntax.as_ref().map(Syntax::from_lit) {
+        None => Syntax::Default,
+        Some(Ok(syntax)) => syntax,
+        Some(Err(_)) if fallback.is_some() => return Vec::new(),
+        Some(Err(error)) => {
+            errors.push(error);
+            return Vec::new();
+        }
+    };
+    let content = greeting.content.as_ref();
+    if fallback.is_some() {
+        return match data {
+            ast::Data::Struct(fields) => {
+                vec![content.map(|content| content.case(None, fields, syntax, false, errors))]
+            }
+            ast::Data::Enum(variants) => variants
+                .iter()
+                .map(|variant| {
+                    let own = variant.content(greeting);
+                    let content = own.or(content)?;
+                    Some(content.case(
+                        Some(&variant.ident),
+                        &variant.fields,
+                        syntax,
+                        own.is_none(),
+                        errors,
+                    ))
+                })
+                .collect(),
+        };
+    }
+    let variant_of = match &greeting.name {
+        Some(name) => format!("greeting `{name}` of variant"),
+        None => "variant".to_string(),
+    };
+    match data {
+        ast::Data::Struct(fields) => {
+            let case = match content {
+                Some(content) => Some(content.case(None, fields, syntax, false, errors)),
+                None if fields.is_empty() => Some(Case::new(
+                    None,
+                    fields,
+                    Template::text(humanize(&input.ident.to_string())),
+                    false,
+                    Span::call_site(),
+                )),
+                None => {
+                    errors.push(match &greeting.name {
+                        Some(name) => {
+                            Error::custom(format!("missing `content` for greeting `{name}`"))
+                                .with_span(name)
+                        }
+                        None => Error::missing_field("content").with_span(&input.ident),
+                    });
+                    None
+                }
+            };
+            vec![case]
+        }
+        ast::Data::Enum(variants) => variants
+            .iter()
+            .filter_map(|variant| {
+                let own = variant.content(greeting);
+                let case = match own.or(content) {
+                    Some(content) => content.case(
+                        Some(&variant.ident),
+                        &variant.fields,
+                        syntax,
+                        own.is_none(),
+                        errors,
+                    ),
+                    None if variant.fields.is_empty() => Case::new(
+                        Some(&variant.ident),
+                        &variant.fields,
+                        Template::text(humanize(&variant.ident.to_string())),
+                        false,
+                        Span::call_site(),
+                    ),
+                    None => {
+                        errors.push(
+                            Error::custom(format!(
+                                "missing `content` for {variant_of} `{}`: only unit variants have a default greeting",
+                                variant.ident
+                            ))
+                            .with_span(&variant.ident),
+                        );
+                        return None;
+                    }
+                };
+                Some(Some(case))
+            })
+            .collect(),
+    }
+}
+
+/// Checks that the template of `case` in `translation` uses the same fields
+/// as the one of `greeting`, so every locale shows the same information.
+fn check_fields(
+    greeting: &Greeting,
+    translation: &Greeting,
+    (case, lowered): (&Case, &Lowered),
+    (translated, translated_lowered): (&Case, &Lowered),
+    errors: &mut darling::error::Accumulator,
+) {
+    let expected = case.used_names(lowered);
+    let found = translated.used_names(translated_lowered);
+    let locale = translation
+        .locale
+        .as_ref()
+        .expect("translations have a locale");
+    let mut template = format!("the `{}` template", locale.value());
+    if let Some(name) = &greeting.name {
+        template += &format!(" of greeting `{name}`");
+    }
+    if let Some(variant) = case.variant() {
+        template += &format!(" of variant `{variant}`");
+    }
+    let reference = match &greeting.default_locale {
+        Some(locale) => format!("the `{}` template", locale.value()),
+        None => "the default template".to_string(),
+    };
+    let extra = found
+        .iter()
+        .filter(|name| !expected.contains(name))
+        .map(|name| format!("{template} uses `{name}`, which {reference} does not"));
+    let missing = expected
+        .iter()
+        .filter(|name| !found.contains(name))
+        .map(|name| format!("{template} does not use `{name}`, which {reference} does"));
+    for message in extra.chain(missing) {
+        let error = Error::custom(format!("{message}: every locale must use the same fields"))
+            .with_span(&translated.span());
+        errors.push(annotate(translated.source(), error));
+    }
+}
+
+/// A bound such as `T: Display`, or `T: Count` for plurals, for every type
+/// the templates format or count that mentions one of the type parameters.
+/// Type parameters that no placeholder reaches stay unbounded, and so do
+/// fields formatted `with` a function and values whose type is unknown, such
+/// as expressions.
+fn format_bounds<'l>(
+    generics: &Generics,
+    lowered: impl Iterator<Item = &'l Lowered>,
+) -> Vec<WherePredicate> {
+    let params = generics
+        .type_params()
+        .map(|param| param.ident.clone())
+        .collect::<Vec<_>>();
+    let mut bounds = Vec::new();
+    if params.is_empty() {
+        return bounds;
+    }
+
+    for (ty, bound) in lowered.flat_map(|lowered| &lowered.bounds) {
+        if !mentions_any(quote!(#ty), &params) {
+            continue;
+        }
+        let predicate = syn::parse_quote!(#ty: #bound);
+        if !bounds.contains(&predicate) {
+            bounds.push(predicate);
+        }
+    }
+    bounds
+}
+
+/// Whether `tokens` contain any of the identifiers in `idents`.
+fn mentions_any(tokens: TokenStream, idents: &[Ident]) -> bool {
+    tokens.into_iter().any(|tree| match tree {
+        TokenTree::Ident(ident) => idents.contains(&ident),
+        TokenTree::Group(group) => mentions_any(group.stream(), idents),
+        _ => false,
+    })
+}
+
+/// The type of the items a collection of type `ty` holds, for vectors, sets,
+/// slices, arrays and references to them.
+pub(crate) fn item_type(ty: &Type) -> Option<&Type> {
+    match ty {
+        Type::Reference(reference) => item_type(&reference.elem),
+        Type::Slice(slice) => Some(&slice.elem),
+        Type::Array(array) => Some(&array.elem),
+        Type::Path(path) if path.qself.is_none() => {
+            let last = path.path.segments.last()?;
+            let collections = [
+                "Vec",
+                "VecDeque",
+                "LinkedList",
+                "HashSet",
+                "BTreeSet",
+                "BinaryHeap",
+            ];
+            if !collections.iter().any(|name| last.ident == name) {
+                return None;
+            }
+            match &last.arguments {
+                syn::PathArguments::AngleBracketed(args) => match args.args.first()? {
+                    syn::GenericArgument::Type(inner) => Some(inner),
+                    _ => None,
+                },
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// The `T` of an `Option<T>` spelled as `Option`, `std::option::Option` or
+/// `core::option::Option`.
+pub(crate) fn option_inner(ty: &Type) -> Option<&Type> {
+    let Type::Path(path) = ty else {
+        return None;
+    };
+    if path.qself.is_some() {
+        return None;
+    }
+    let segments = path.path.segments.iter().collect::<Vec<_>>();
+    let (last, prefix) = segments.split_last()?;
+    let prefix_ok = match prefix {
+        [] => true,
+        [krate, module] => {
+            (krate.ident == "std" || krate.ident == "core") && module.ident == "option"
+        }
+        _ => false,
+    };
+    if last.ident != "Option" || !prefix_ok {
+        return None;
+    }
+    match &last.arguments {
+        syn::PathArguments::AngleBracketed(args) if args.args.len() == 1 => match &args.args[0] {
+            syn::GenericArgument::Type(inner) => Some(inner),
+            _ => None,
+        },
+        _ => None,
+    }
+}
@@ -1,118 +1,825 @@
 use proc_macro::TokenStream;
 
-use darling::{export::NestedMeta, FromDeriveInput, FromMeta};
+use darling::{
+    ast, export::NestedMeta, util::Flag, FromDeriveInput, FromField, FromMeta, FromVariant,
+};
 use quote::quote;
-use syn::{parse_macro_input, DeriveInput};
+use syn::{parse_macro_input, DeriveInput, Ident, LitStr, Path, Type, Visibility};
+use template::DEFAULT_CONTENT;
+
+use crate::codegen::{
+    describe, impl_greet, locale_key, option_inner, same_locale, Content, Greeting, Variant,
+};
+use crate::source::TemplateFile;
+
+mod codegen;
+mod lower;
+mod source;
+
+/// Per-field `#[greet(...)]` options, understood by every macro. Fields of a
+/// `#[derive(Greet2)]` type may also use `#[greet2(...)]`.
+#[derive(Debug, FromField)]
+#[darling(attributes(greet, greet2), and_then = FieldArgs::validate)]
+pub(crate) struct FieldArgs {
+    pub(crate) ident: Option<Ident>,
+    pub(crate) ty: Type,
+    /// Forbids the field in templates.
+    pub(crate) skip: Flag,
+    /// The placeholder name the field is known by instead of its own.
+    pub(crate) rename: Option<LitStr>,
+    /// A `fn(&T, &mut fmt::Formatter) -> fmt::Result` formatting the field.
+    pub(crate) with: Option<Path>,
+    /// Written instead of an `Option` field's value when it is `None`.
+    pub(crate) default: Option<LitStr>,
+}
+
+impl FieldArgs {
+    fn validate(self) -> darling::Result<Self> {
+        let mut errors = darling::Error::accumulator();
+        if let Some(rename) = &self.rename {
+            if syn::parse_str::<Ident>(&rename.value()).is_err() {
+                errors.push(
+                    darling::Error::custom("`rename` must be a valid identifier").with_span(rename),
+                );
+            }
+        }
+        if let Some(default) = &self.default {
+            if option_inner(&self.ty).is_none() {
+                errors.push(
+                    darling::Error::custom("`default` can only be used on `Option` fields")
+                        .with_span(default),
+                );
+            }
+        }
+        errors.finish_with(self)
+    }
+}
+
+/// Parses the value of option `option`, which must be usable as a method
+/// name.
+fn method_name(lit: Option<LitStr>, option: &str) -> darling::Result<Option<Ident>> {
+    let Some(lit) = lit else {
+        return Ok(None);
+    };
+    match syn::parse_str::<Ident>(&lit.value()) {
+        Ok(ident) => Ok(Some(Ident::new(&ident.to_string(), lit.span()))),
+        Err(_) => Err(
+            darling::Error::custom(format!("`{option}` must be a valid identifier"))
+                .with_span(&lit),
+        ),
+    }
+}
+
+/// Per-variant `#[greet(content = "...")]` of an enum.
+#[derive(Debug, FromVariant)]
+#[darling(attributes(greet))]
+struct GreetVariantArgs {
+    ident: Ident,
+    fields: ast::Fields<FieldArgs>,
+    content: Option<LitStr>,
+}
+
+impl From<GreetVariantArgs> for Variant {
+    fn from(v: GreetVariantArgs) -> Self {
+        Variant {
+            ident: v.ident,
+            fields: v.fields,
+            contents: v
+                .content
+                .map(|content| (None, None, content.into()))
+                .into_iter()
+                .collect(),
+        }
+    }
+}
+
+/// The options of a greeting besides its template, understood by every
+/// macro.
+#[derive(Debug, Default, FromMeta)]
+struct GreetingOptions {
+    /// Also implement `Display` with the greeting.
+    display: Flag,
+    /// Also generate inherent methods of this name. Named greetings are
+    /// greeted by methods of their `name` unless it is set.
+    method: Option<LitStr>,
+    /// The visibility of those methods, the type's own by default.
+    vis: Option<Visibility>,
+    /// Chooses plural forms instead of `greet::plural::english`.
+    plural_rules: Option<Path>,
+    /// `"icu"` to write templates in ICU MessageFormat. `Greet2` applies it
+    /// to every template of the type.
+    syntax: Option<LitStr>,
+    /// A module of filters for placeholders, besides the built-in ones.
+    /// `Greet2` applies it to every template of the type.
+    filters: Option<Path>,
+}
+
+impl GreetingOptions {
+    /// The unnamed greeting of `content` with these options.
+    fn greeting(self, content: Option<LitStr>) -> darling::Result<Greeting> {
+        Ok(Greeting {
+            name: None,
+            content: content.map(Content::from),
+            display: self.display.is_present(),
+            method: method_name(self.method, "method")?,
+            vis: self.vis,
+            plural_rules: self.plural_rules,
+            syntax: self.syntax,
+            filters: self.filters,
+            ..Greeting::new(None)
+        })
+    }
+}
+
+#[derive(Debug, FromDeriveInput)]
+#[darling(attributes(greet))]
+struct GreetDeriveArgs {
+    data: ast::Data<GreetVariantArgs, FieldArgs>,
+    content: Option<LitStr>,
+    #[darling(flatten)]
+    options: GreetingOptions,
+}
+
+impl GreetDeriveArgs {
+    /// Generates the Greet implementation. Structs with named fields and no
+    /// template of their own use [`DEFAULT_CONTENT`].
+    fn impl_greet(self, input: &DeriveInput) -> darling::Result<proc_macro2::TokenStream> {
+        let content = match (self.content, &self.data) {
+            (None, ast::Data::Struct(fields)) if fields.is_struct() => {
+                Some(LitStr::new(DEFAULT_CONTENT, proc_macro2::Span::call_site()))
+            }
+            (content, _) => content,
+        };
+        let greeting = self.options.greeting(content)?;
+        let data = self.data.map_enum_variants(Variant::from);
+        impl_greet(input, &[greeting], &data)
+    }
+}
 
 #[proc_macro]
 pub fn add_greet(input: TokenStream) -> TokenStream {
-    // Parse the input struct definition
+    // Parse the input item definition
     let input = parse_macro_input!(input as DeriveInput);
 
-    // Extract the struct name and fields
-    let struct_name = input.ident;
-    let fields = match input.data {
-        syn::Data::Struct(ref s) => s.fields.iter().collect::<Vec<_>>(),
-        _ => panic!("Greet can only be derived for structs"),
-    };
+    // Extract the greeting templates and generate the Greet implementation
+    let greet_impl = GreetDeriveArgs::from_derive_input(&input)
+        .and_then(|args| args.impl_greet(&input))
+        .unwrap_or_else(|e| write_errors(e, &input));
 
-    // Generate the new struct definition with the greet method
+    // Re-emit the item as written, minus the `#[greet(...)]` attributes only
+    // this macro understands, followed by its Greet implementation
+    let mut item = input.clone();
+    strip_attrs(&mut item, "greet");
     let expanded = quote! {
-        struct #struct_name {
-            #(#fields),*
-        }
+        #item
 
-        impl #struct_name {
-            fn greet(&self) {
-                println!("Hello, my name is {} and I am {} years old.", self.name, self.age);
-            }
-        }
+        #greet_impl
     };
 
     // Return the generated code as a TokenStream
     TokenStream::from(expanded)
 }
 
-#[proc_macro_derive(Greet)]
+#[proc_macro_derive(Greet, attributes(greet))]
 pub fn greet_derive(input: TokenStream) -> TokenStream {
     // Parse the input struct definition
     let input = parse_macro_input!(input as DeriveInput);
 
-    // Extract the struct name and fields
-    let struct_name = input.ident;
-
-    // Generate the new struct definition with the greet method
-    let expanded = quote! {
-        impl #struct_name {
-            fn greet(&self) {
-                println!("Hello, my name is {} and I am {} years old.", self.name, self.age);
-            }
-        }
-    };
+    // Implement the Greet trait for the existing struct or enum
+    let expanded = GreetDeriveArgs::from_derive_input(&input)
+        .and_then(|args| args.impl_greet(&input))
+        .unwrap_or_else(|e| write_errors(e, &input));
 
     // Return the generated code as a TokenStream
     TokenStream::from(expanded)
 }
 
-#[derive(Debug, FromMeta)]
+#[derive(Debug, Default, FromMeta)]
 struct GreetArgs {
-    content: String,
+    content: Option<LitStr>,
+    #[darling(flatten)]
+    options: GreetingOptions,
 }
 
-/// Example #[greet(content = "Hello, my name is {self.name} and I am {self.age} years old.")]
+/// Example #[greet(content = "Hello, my name is {name} and I am {age} years old.")]
+///
+/// On an enum, each variant may carry its own `#[greet(content = "...")]`.
+/// Add `display` to also implement `Display` with the greeting, and
+/// `method = "introduce"` (optionally with `vis = "pub(crate)"`) to also
+/// generate `introduce()`, `introduce_string()` and `write_introduce(w)`, and
+/// `plural_rules = greet::plural::french` to choose plural forms by other
+/// rules than English ones. `syntax = "icu"` reads the templates as ICU
+/// MessageFormat, and `filters = path::to::module` makes the filter functions
+/// of a module available to placeholders.
 #[proc_macro_attribute]
 pub fn greet(args: TokenStream, input: TokenStream) -> TokenStream {
-    let attr_args = match NestedMeta::parse_meta_list(args.into()) {
-        Ok(v) => v,
-        Err(e) => {
-            return TokenStream::from(darling::Error::from(e).write_errors());
-        }
-    };
-    let greet_args = match GreetArgs::from_list(&attr_args) {
-        Ok(v) => v,
-        Err(e) => {
-            return TokenStream::from(e.write_errors());
-        }
-    };
+    let mut input = parse_macro_input!(input as DeriveInput);
 
-    let input = parse_macro_input!(input as DeriveInput);
-    let struct_name = input.ident.clone();
-    let content = greet_args.content;
+    // Collect every problem with the arguments and variants before giving up
+    let mut errors = darling::Error::accumulator();
+    let greet_args = NestedMeta::parse_meta_list(args.into())
+        .map_err(darling::Error::from)
+        .and_then(|attr_args| GreetArgs::from_list(&attr_args));
+    let greet_args = errors.handle(greet_args).unwrap_or_default();
+    let data = errors
+        .handle(ast::Data::<GreetVariantArgs, FieldArgs>::try_from(
+            &input.data,
+        ))
+        .map(|data| data.map_enum_variants(Variant::from));
+    let greet_impl = errors
+        .finish()
+        .and_then(|()| {
+            let data = data.expect("parsed without errors");
+            let greeting = greet_args.options.greeting(greet_args.content)?;
+            impl_greet(&input, &[greeting], &data)
+        })
+        .unwrap_or_else(|e| write_errors(e, &input));
 
+    strip_attrs(&mut input, "greet");
     let expanded = quote! {
         #input
 
-        impl #struct_name {
-            fn greet(&self) {
-                println!(#content, name = self.name, age = self.age);
-            }
-        }
+        #greet_impl
     };
 
     TokenStream::from(expanded)
 }
 
+/// One `#[greet2(...)]` attribute of a type. A type may carry several, one
+/// per named greeting.
+#[derive(Debug, FromMeta)]
+struct Greet2Attr {
+    /// The greeting the attribute configures, the primary one if absent.
+    name: Option<LitStr>,
+    content: Option<LitStr>,
+    #[darling(flatten)]
+    options: GreetingOptions,
+    /// Makes `content` and `plural_rules` those of the greeting in this
+    /// locale, e.g. `"vi"`.
+    locale: Option<LitStr>,
+    /// The locale of the templates without `locale`, tried last.
+    default_locale: Option<LitStr>,
+    /// The locales to try for the first one, e.g. `"vi-VN > vi > en"`,
+    /// instead of it and its parents.
+    fallback: Option<LitStr>,
+    /// A TOML file of templates for the type, relative to the crate's
+    /// manifest directory.
+    path: Option<LitStr>,
+    /// Reads `content` from `path` at this dotted key.
+    key: Option<LitStr>,
+}
+
+/// The template an attribute gives.
+#[derive(Debug)]
+enum TemplateArg {
+    Content(Content),
+    /// The key of a template in the type's `path`, until it is read.
+    Key(LitStr),
+}
+
+impl TemplateArg {
+    /// The `content` or `key` of an attribute, which cannot have both.
+    fn from_attr(content: Option<LitStr>, key: Option<LitStr>) -> darling::Result<Option<Self>> {
+        match (content, key) {
+            (Some(_), Some(key)) => Err(darling::Error::custom(
+                "`content` and `key` both give the template: keep one of them",
+            )
+            .with_span(&key)),
+            (Some(content), None) => Ok(Some(Self::Content(content.into()))),
+            (None, key) => Ok(key.map(Self::Key)),
+        }
+    }
+
+    /// Reads the template of a `key` from `file`. `path_given` tells a
+    /// missing `path` from one that could not be read, which is already
+    /// reported.
+    fn read(
+        self,
+        file: Option<&TemplateFile>,
+        path_given: bool,
+        errors: &mut darling::error::Accumulator,
+    ) -> Option<Content> {
+        match (self, file) {
+            (Self::Content(content), _) => Some(content),
+            (Self::Key(key), Some(file)) => {
+                errors
+                    .handle(file.template(&key))
+                    .map(|(lit, source)| Content {
+                        lit,
+                        source: Some(source),
+                    })
+            }
+            (Self::Key(key), None) => {
+                if !path_given {
+                    errors.push(
+                        darling::Error::custom(
+                            "`key` reads the template from a file: give the file with `path = \"...\"`",
+                        )
+                        .with_span(&key),
+                    );
+                }
+                None
+            }
+        }
+    }
+
+    fn span(&self) -> proc_macro2::Span {
+        match self {
+            Self::Content(content) => content.lit.span(),
+            Self::Key(key) => key.span(),
+        }
+    }
+}
+
+/// One `#[greet2(...)]` attribute of an enum variant.
+#[derive(Debug, FromMeta)]
+struct Greet2VariantAttr {
+    name: Option<LitStr>,
+    content: Option<LitStr>,
+    locale: Option<LitStr>,
+    /// Reads `content` from the type's `path` at this dotted key.
+    key: Option<LitStr>,
+}
+
+#[derive(Debug, FromVariant)]
+#[darling(forward_attrs(greet2, greet), and_then = Greet2VariantArgs::parse_attrs)]
+struct Greet2VariantArgs {
+    ident: Ident,
+    fields: ast::Fields<FieldArgs>,
+    attrs: Vec<syn::Attribute>,
+    /// The templates of the variant, by greeting and locale. Keys are read
+    /// by [`Greet2Args::parse_attrs`], which knows the type's `path`.
+    #[darling(skip)]
+    contents: Vec<(Option<Ident>, Option<LitStr>, TemplateArg)>,
+}
+
+impl Greet2VariantArgs {
+    fn parse_attrs(mut self) -> darling::Result<Self> {
+        let mut errors = darling::Error::accumulator();
+        for attr in &self.attrs {
+            if !is_greet2(attr, &mut errors) {
+                continue;
+            }
+            let Some(attr) = errors.handle(Greet2VariantAttr::from_meta(&attr.meta)) else {
+                continue;
+            };
+            let Some(name) = errors.handle(method_name(attr.name, "name")) else {
+                continue;
+            };
+            let Some(locale) = errors.handle(attr.locale.map(parse_locale).transpose()) else {
+                continue;
+            };
+            let Some(content) = errors
+                .handle(TemplateArg::from_attr(attr.content, attr.key))
+                .flatten()
+            else {
+                continue;
+            };
+            if self.contents.iter().any(|(other, other_locale, _)| {
+                *other == name && same_locale(other_locale.as_ref(), locale.as_ref())
+            }) {
+                let of = match &locale {
+                    Some(locale) => format!("the `{}` translation of ", locale.value()),
+                    None => String::new(),
+                };
+                errors.push(
+                    darling::Error::custom(format!(
+                        "duplicate `content` for {of}{} of variant `{}`",
+                        describe(&name),
+                        self.ident
+                    ))
+                    .with_span(&content.span()),
+                );
+                continue;
+            }
+            self.contents.push((name, locale, content));
+        }
+        errors.finish_with(self)
+    }
+}
+
+impl From<Greet2VariantArgs> for Variant {
+    fn from(v: Greet2VariantArgs) -> Self {
+        Variant {
+            ident: v.ident,
+            fields: v.fields,
+            contents: v
+                .contents
+                .into_iter()
+                .filter_map(|(name, locale, template)| match template {
+                    TemplateArg::Content(content) => Some((name, locale, content)),
+                    TemplateArg::Key(_) => None,
+                })
+                .collect(),
+        }
+    }
+}
+
 #[derive(Debug, FromDeriveInput)]
-#[darling(attributes(greet2))]
+#[darling(forward_attrs(greet2, greet), and_then = Greet2Args::parse_attrs)]
 struct Greet2Args {
-    content: String,
+    data: ast::Data<Greet2VariantArgs, FieldArgs>,
+    attrs: Vec<syn::Attribute>,
+    /// Every greeting of the type, the attributes of the same greeting
+    /// merged.
+    #[darling(skip)]
+    greetings: Vec<Greeting>,
+    /// The file given by `path`.
+    #[darling(skip)]
+    file: Option<TemplateFile>,
 }
 
-#[proc_macro_derive(Greet2, attributes(greet2))]
-pub fn greet2(input: TokenStream) -> TokenStream {
-    let input = parse_macro_input!(input as DeriveInput);
-    let greet_attr_args = Greet2Args::from_derive_input(&input).unwrap();
+impl Greet2Args {
+    fn parse_attrs(mut self) -> darling::Result<Self> {
+        let mut errors = darling::Error::accumulator();
+        let mut display = None;
+        let mut syntax = None;
+        let mut filters = None;
+        let mut default_locale = None;
+        let mut fallbacks: Vec<Vec<String>> = Vec::new();
+        let attrs = self
+            .attrs
+            .iter()
+            .filter_map(|attr| {
+                is_greet2(attr, &mut errors)
+                    .then(|| errors.handle(Greet2Attr::from_meta(&attr.meta)))
+                    .flatten()
+            })
+            .collect::<Vec<_>>();
+        // The file is needed by the attributes before the one naming it
+        let mut path = None;
+        for lit in attrs.iter().filter_map(|attr| attr.path.clone()) {
+            set_once(&mut path, lit, "path", "the type", &mut errors);
+        }
+        let path_given = path.is_some();
+        self.file = path.and_then(|path| errors.handle(TemplateFile::open(path)));
+        for attr in attrs {
+            let Some(name) = errors.handle(method_name(attr.name, "name")) else {
+                continue;
+            };
+            let index = match self.greetings.iter().position(|g| g.name == name) {
+                Some(index) => index,
+                None => {
+                    self.greetings.push(Greeting::new(name));
+                    self.greetings.len() - 1
+                }
+            };
+            if let Some(lit) = attr.options.syntax {
+                set_once(&mut syntax, lit, "syntax", "the type", &mut errors);
+            }
+            if let Some(module) = attr.options.filters {
+                set_once(&mut filters, module, "filters", "the type", &mut errors);
+            }
+            if let Some(locale) = errors
+                .handle(attr.default_locale.map(parse_locale).transpose())
+                .flatten()
+            {
+                set_once(
+                    &mut default_locale,
+                    locale,
+                    "default_locale",
+                    "the type",
+                    &mut errors,
+                );
+            }
+            if let Some(lit) = attr.fallback {
+                match parse_fallback(&lit) {
+                    Ok(chain)
+                        if fallbacks
+                            .iter()
+                            .any(|other| locale_key(&other[0]) == locale_key(&chain[0])) =>
+                    {
+                        errors.push(
+                            darling::Error::custom(format!(
+                                "duplicate `fallback` for `{}`",
+                                chain[0]
+                            ))
+                            .with_span(&lit),
+                        );
+                    }
+                    Ok(chain) => fallbacks.push(chain),
+                    Err(error) => errors.push(error),
+                }
+            }
+            let greeting = &mut self.greetings[index];
+            let Some(locale) = errors.handle(attr.locale.map(parse_locale).transpose()) else {
+                continue;
+            };
+            let content = errors
+                .handle(TemplateArg::from_attr(attr.content, attr.key))
+                .flatten()
+                .and_then(|content| content.read(self.file.as_ref(), path_given, &mut errors));
+            if let Some(locale) = locale {
+                // Only the template and its plural rules vary by locale
+                let per_greeting = [
+                    ("method", attr.options.method.is_some()),
+                    ("vis", attr.options.vis.is_some()),
+                    ("display", attr.options.display.is_present()),
+                ];
+                for (option, _) in per_greeting.iter().filter(|(_, given)| *given) {
+                    errors.push(
+                        darling::Error::custom(format!(
+                            "`{option}` cannot be set per locale: set it in the attribute without `locale`"
+                        ))
+                        .with_span(&locale),
+                    );
+                }
+                let translation = greeting.translation(locale);
+                let describe_translation = format!(
+                    "the `{}` translation of {}",
+                    translation
+                        .locale
+                        .as_ref()
+                        .expect("translations have a locale")
+                        .value(),
+                    describe(&translation.name)
+                );
+                if let Some(content) = content {
+                    set_once(
+                        &mut translation.content,
+                        content,
+                        "content",
+                        &describe_translation,
+                        &mut errors,
+                    );
+                }
+                if let Some(rules) = attr.options.plural_rules {
+                    set_once(
+                        &mut translation.plural_rules,
+                        rules,
+                        "plural_rules",
+                        &describe_translation,
+                        &mut errors,
+                    );
+                }
+                continue;
+            }
+            if let Some(content) = content {
+                if greeting.content.is_some() {
+                    let error = match &greeting.name {
+                        Some(name) => format!("duplicate greeting `{name}`"),
+                        None => "duplicate `content` for the unnamed greeting: give additional greetings a `name`".to_string(),
+                    };
+                    errors.push(darling::Error::custom(error).with_span(&content));
+                } else {
+                    greeting.content = Some(content);
+                }
+            }
+            let describe_greeting = describe(&greeting.name);
+            if let Some(method) = errors
+                .handle(method_name(attr.options.method, "method"))
+                .flatten()
+            {
+                set_once(
+                    &mut greeting.method,
+                    method,
+                    "method",
+                    &describe_greeting,
+                    &mut errors,
+                );
+            }
+            if let Some(vis) = attr.options.vis {
+                set_once(
+                    &mut greeting.vis,
+                    vis,
+                    "vis",
+                    &describe_greeting,
+                    &mut errors,
+                );
+            }
+            if let Some(rules) = attr.options.plural_rules {
+                set_once(
+                    &mut greeting.plural_rules,
+                    rules,
+                    "plural_rules",
+                    &describe_greeting,
+                    &mut errors,
+                );
+            }
+            if attr.options.display.is_present() {
+                match &display {
+                    Some(other) if *other != greeting.name => errors.push(
+                        darling::Error::custom(format!(
+                            "only one greeting can implement `Display`, and {} already does",
+                            describe(other)
+                        ))
+                        .with_span(&attr.options.display.span()),
+                    ),
+                    _ => {
+                        greeting.display = true;
+                        display = Some(greeting.name.clone());
+                    }
+                }
+            }
+        }
 
-    let struct_name = input.ident;
-    let content = greet_attr_args.content;
+        // Greetings and their translations may also be introduced by
+        // variants alone, and a type without any greeting attribute gets the
+        // unnamed one.
+        if let ast::Data::Enum(variants) = &mut self.data {
+            for (_, _, template) in variants.iter_mut().flat_map(|v| &mut v.contents) {
+                if let TemplateArg::Key(key) = template {
+                    let key = TemplateArg::Key(key.clone());
+                    if let Some(content) = key.read(self.file.as_ref(), path_given, &mut errors) {
+                        *template = TemplateArg::Content(content);
+                    }
+                }
+            }
+            for (name, locale, _) in variants.iter().flat_map(|v| &v.contents) {
+                let index = match self.greetings.iter().position(|g| g.name == *name) {
+                    Some(index) => index,
+                    None => {
+                        self.greetings.push(Greeting::new(name.clone()));
+                        self.greetings.len() - 1
+                    }
+                };
+                if let Some(locale) = locale {
+                    self.greetings[index].translation(locale.clone());
+                }
+            }
+        }
+        if self.greetings.is_empty() {
+            self.greetings.push(Greeting::new(None));
+        }
 
-    let expanded = quote! {
-        impl #struct_name {
-            fn greet(&self) {
-                println!(#content, name = self.name, age = self.age);
+        // The default locale's templates are the ones without `locale`
+        if let Some(default_locale) = &default_locale {
+            let variant_locales = match &self.data {
+                ast::Data::Enum(variants) => variants
+                    .iter()
+                    .flat_map(|v| &v.contents)
+                    .filter_map(|(_, locale, _)| locale.as_ref())
+                    .collect(),
+                ast::Data::Struct(_) => Vec::new(),
+            };
+            let translation_locales = self
+                .greetings
+                .iter()
+                .flat_map(|g| &g.translations)
+                .filter_map(|t| t.locale.as_ref());
+            for locale in translation_locales.chain(variant_locales) {
+                if same_locale(Some(locale), Some(default_locale)) {
+                    errors.push(
+                        darling::Error::custom(format!(
+                            "`{}` is the default locale: give its `content` without `locale`",
+                            locale.value()
+                        ))
+                        .with_span(locale),
+                    );
+                }
             }
         }
-    };
+        for greeting in &mut self.greetings {
+            greeting.fallbacks.clone_from(&fallbacks);
+            greeting.syntax.clone_from(&syntax);
+            greeting.filters.clone_from(&filters);
+            greeting.default_locale.clone_from(&default_locale);
+            for translation in &mut greeting.translations {
+                translation.syntax.clone_from(&syntax);
+                translation.filters.clone_from(&filters);
+                translation.default_locale.clone_from(&default_locale);
+            }
+        }
+        errors.finish_with(self)
+    }
+}
+
+/// Whether `attr` is a `#[greet2(...)]`. `greet` is a helper attribute of
+/// `Greet2` only for the fields, so that they can share `#[greet(skip)]`
+/// and the like with the other macros; on the type and its variants it is
+/// reported rather than ignored.
+fn is_greet2(attr: &syn::Attribute, errors: &mut darling::error::Accumulator) -> bool {
+    if attr.path().is_ident("greet") {
+        errors.push(
+            darling::Error::custom(
+                "`#[greet(...)]` configures `Greet`, not `Greet2`: use `#[greet2(...)]`",
+            )
+            .with_span(attr.path()),
+        );
+        return false;
+    }
+    true
+}
+
+/// Checks that `lit` is a locale tag such as `vi` or `vi-VN`.
+fn parse_locale(lit: LitStr) -> darling::Result<LitStr> {
+    if is_locale(&lit.value()) {
+        Ok(lit)
+    } else {
+        Err(darling::Error::custom(format!(
+            "invalid locale `{}`: expected a tag such as `vi` or `vi-VN`",
+            lit.value()
+        ))
+        .with_span(&lit))
+    }
+}
+
+fn is_locale(locale: &str) -> bool {
+    locale
+        .split(['-', '_'])
+        .all(|subtag| !subtag.is_empty() && subtag.chars().all(|c| c.is_ascii_alphanumeric()))
+}
+
+/// Parses a fallback chain such as `"vi-VN > vi > en"` into its locales.
+fn parse_fallback(lit: &LitStr) -> darling::Result<Vec<String>> {
+    let value = lit.value();
+    let chain = value
+        .split('>')
+        .map(|locale| locale.trim().to_string())
+        .collect::<Vec<_>>();
+    if chain.len() < 2 || !chain.iter().all(|locale| is_locale(locale)) {
+        return Err(darling::Error::custom(format!(
+            "invalid fallback `{value}`: expected locales separated by `>`, e.g. `vi-VN > vi > en`"
+        ))
+        .with_span(lit));
+    }
+    Ok(chain)
+}
+
+/// Sets `slot` to `value`, or reports that `option` was already given for
+/// `greeting` by another attribute.
+fn set_once<T: quote::ToTokens>(
+    slot: &mut Option<T>,
+    value: T,
+    option: &str,
+    greeting: &str,
+    errors: &mut darling::error::Accumulator,
+) {
+    if slot.is_some() {
+        errors.push(
+            darling::Error::custom(format!("duplicate `{option}` for {greeting}"))
+                .with_span(&value),
+        );
+    } else {
+        *slot = Some(value);
+    }
+}
+
+/// Example #[greet2(content = "Hello, my name is {name} and I am {age} years old.")]
+///
+/// Repeat the attribute with a `name` for additional greetings, e.g.
+/// `#[greet2(name = "farewell", content = "Bye, {name}!")]` generates
+/// `farewell()`, `farewell_string()` and `write_farewell(w)`. `method` and
+/// `vis` rename those methods and set their visibility.
+///
+/// Repeat it with a `locale` to translate a greeting, e.g.
+/// `#[greet2(locale = "vi", content = "Xin chào, tôi là {name}.")]`, which
+/// generates `greet_in(locale)`, `greeting_in(locale)` and
+/// `write_greeting_in(locale, w)`, or `farewell_in(locale)` and so on for
+/// named greetings. A locale such as `vi-VN` falls back to `vi`, then to the
+/// templates without `locale`, whose locale `default_locale = "en"` names;
+/// `fallback = "pt-BR > pt-PT > es"` sets another chain for a locale.
+///
+/// Templates can also be read from a TOML file: `path = "greetings.toml"`,
+/// relative to the crate's manifest, names it for the whole type, and
+/// `key = "person.welcome"` takes the place of `content` in any attribute.
+#[proc_macro_derive(Greet2, attributes(greet2, greet))]
+pub fn greet2(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    let expanded = Greet2Args::from_derive_input(&input)
+        .and_then(|args| {
+            let track = args.file.as_ref().map(TemplateFile::track);
+            let data = args.data.map_enum_variants(Variant::from);
+            let greet_impl = impl_greet(&input, &args.greetings, &data)?;
+            Ok(quote! {
+                #greet_impl
+                #track
+            })
+        })
+        .unwrap_or_else(|e| write_errors(e, &input));
 
     TokenStream::from(expanded)
 }
+
+/// Removes the attributes called `name` from `item`, its variants and its
+/// fields. They only configure our macros and mean nothing to the compiler.
+fn strip_attrs(item: &mut DeriveInput, name: &str) {
+    let is_ours = |attr: &syn::Attribute| attr.path().is_ident(name);
+    item.attrs.retain(|attr| !is_ours(attr));
+    let fields = match &mut item.data {
+        syn::Data::Struct(s) => s.fields.iter_mut().collect::<Vec<_>>(),
+        syn::Data::Enum(e) => e
+            .variants
+            .iter_mut()
+            .flat_map(|variant| {
+                variant.attrs.retain(|attr| !is_ours(attr));
+                variant.fields.iter_mut()
+            })
+            .collect(),
+        syn::Data::Union(u) => u.fields.named.iter_mut().collect(),
+    };
+    for field in fields {
+        field.attrs.retain(|attr| !is_ours(attr));
+    }
+}
+
+/// Turns `errors` into `compile_error!`s. Errors that carry no span of their
+/// own, such as darling's complaint about unions, point at `item`'s name.
+fn write_errors(errors: darling::Error, item: &DeriveInput) -> proc_macro2::TokenStream {
+    let errors = errors
+        .into_iter()
+        .map(|error| error.with_span(&item.ident))
+        .collect();
+    darling::Error::multiple(errors).write_errors()
+}
@@ -0,0 +1,1030 @@
+//! Lowering of a parsed template into the statements writing it.
+//!
+//! Names in a template are resolved here: against the values sections make
+//! available first, then against the fields of the struct or variant being
+//! greeted. Everything wrong with them is reported in the same pass.
+
+use darling::{ast, Error};
+use proc_macro2::{Ident, Span, TokenStream};
+use quote::{format_ident, quote, quote_spanned};
+use syn::{
+    ext::IdentExt,
+    parse_quote, parse_quote_spanned,
+    spanned::Spanned,
+    visit::{self, Visit},
+    visit_mut::{self, VisitMut},
+    Block, Expr, Lit, Member, Pat, PatIdent, Stmt, Type,
+};
+use template::{
+    member_name, plain_name, respan, Argument, Each, Filter, Placeholder, Segment, Template,
+    PLURAL_CATEGORIES,
+};
+
+use crate::codegen::{item_type, option_inner, Greeting};
+use crate::source::{annotate, Source};
+use crate::FieldArgs;
+
+/// One shape a value can take, the struct itself or one enum variant,
+/// together with the template that greets it.
+pub(crate) struct Case<'a> {
+    /// `None` for a struct.
+    variant: Option<&'a Ident>,
+    fields: &'a ast::Fields<FieldArgs>,
+    template: Template,
+    /// Whether a variant borrowed the enum's template instead of having its own.
+    inherited: bool,
+    /// Where the template was written.
+    span: Span,
+    /// Where the template is, if it was read from a file.
+    source: Option<Source>,
+}
+
+/// A field of a [`Case`] and the name templates know it by.
+struct CaseField<'a> {
+    member: Member,
+    name: String,
+    args: &'a FieldArgs,
+}
+
+/// A case's template, ready to be written.
+pub(crate) struct Lowered {
+    /// Statements writing the template to `f`.
+    pub(crate) writes: TokenStream,
+    /// The fields the template reads.
+    used: Vec<Member>,
+    /// The types the template formats or counts, with the trait each needs,
+    /// such as `core::fmt::Display`, as far as they are known.
+    pub(crate) bounds: Vec<(Type, TokenStream)>,
+}
+
+impl<'a> Case<'a> {
+    pub(crate) fn new(
+        variant: Option<&'a Ident>,
+        fields: &'a ast::Fields<FieldArgs>,
+        template: Template,
+        inherited: bool,
+        span: Span,
+    ) -> Self {
+        Self {
+            variant,
+            fields,
+            template,
+            inherited,
+            span,
+            source: None,
+        }
+    }
+
+    /// Marks the template as read from `source`.
+    pub(crate) fn with_source(self, source: Option<Source>) -> Self {
+        Self { source, ..self }
+    }
+
+    pub(crate) fn source(&self) -> Option<&Source> {
+        self.source.as_ref()
+    }
+
+    pub(crate) fn variant(&self) -> Option<&'a Ident> {
+        self.variant
+    }
+
+    pub(crate) fn span(&self) -> Span {
+        self.span
+    }
+
+    /// The names templates know the fields `lowered` reads by.
+    pub(crate) fn used_names(&self, lowered: &Lowered) -> Vec<String> {
+        self.fields()
+            .filter(|field| lowered.used.contains(&field.member))
+            .map(|field| field.name)
+            .collect()
+    }
+
+    /// All fields, skipped ones included. Fields are known by their name, or
+    /// their position for tuple fields, unless renamed.
+    fn fields(&self) -> impl Iterator<Item = CaseField<'a>> {
+        self.fields.iter().enumerate().map(|(i, args)| {
+            let member = match &args.ident {
+                Some(ident) => Member::Named(ident.clone()),
+                None => Member::Unnamed(i.into()),
+            };
+            let name = match &args.rename {
+                Some(rename) => rename.value(),
+                None => member_name(&member),
+            };
+            CaseField { member, name, args }
+        })
+    }
+
+    /// The field templates know as `name`, unless it is skipped.
+    fn field_named(&self, name: &str) -> Option<CaseField<'a>> {
+        self.fields()
+            .find(|field| field.name == name && !field.args.skip.is_present())
+    }
+
+    /// The local a `match self` arm binds `member` to.
+    fn binding(member: &Member) -> Ident {
+        format_ident!("__greet_{}", member_name(member))
+    }
+
+    /// A place expression reading `member`.
+    fn access(&self, member: &Member) -> TokenStream {
+        match self.variant {
+            None => quote!(self.#member),
+            Some(_) => {
+                let binding = Self::binding(member);
+                quote!((*#binding))
+            }
+        }
+    }
+
+    /// Lowers the template of `greeting`, pushing every name that cannot be
+    /// used to `errors`.
+    pub(crate) fn lower(
+        &self,
+        greeting: &Greeting,
+        errors: &mut darling::error::Accumulator,
+    ) -> Lowered {
+        let mut lower = Lower {
+            case: self,
+            greeting,
+            scope: Vec::new(),
+            locals: Vec::new(),
+            used: Vec::new(),
+            bounds: Vec::new(),
+            problems: Vec::new(),
+        };
+        let writes = lower.segments(&self.template.segments);
+
+        let failed = !lower.problems.is_empty();
+        for problem in lower.problems {
+            errors.push(annotate(self.source(), problem));
+        }
+        if let Some(variant) = self.variant.filter(|_| failed && self.inherited) {
+            errors.push(
+                Error::custom(format!(
+                    "variant `{variant}` has no `content` of its own and uses the type-level template"
+                ))
+                .with_span(variant),
+            );
+        }
+
+        Lowered {
+            writes,
+            used: lower.used,
+            bounds: lower.bounds,
+        }
+    }
+
+    /// A `match self` arm for an enum variant, binding only the fields the
+    /// template uses.
+    pub(crate) fn match_arm(&self, lowered: &Lowered) -> TokenStream {
+        let variant = self.variant;
+        let used = &lowered.used;
+        let pattern = match self.fields.style {
+            ast::Style::Struct => {
+                let bindings = self
+                    .fields()
+                    .filter(|field| used.contains(&field.member))
+                    .map(|field| {
+                        let member = &field.member;
+                        let name = Self::binding(member);
+                        quote!(#member: #name)
+                    });
+                quote!({ #(#bindings,)* .. })
+            }
+            ast::Style::Tuple => {
+                let bindings = self.fields().map(|field| {
+                    if used.contains(&field.member) {
+                        let name = Self::binding(&field.member);
+                        quote!(#name)
+                    } else {
+                        quote!(_)
+                    }
+                });
+                quote!((#(#bindings),*))
+            }
+            ast::Style::Unit => quote!(),
+        };
+        let writes = &lowered.writes;
+        quote! {
+            Self::#variant #pattern => {
+                #writes
+            }
+        }
+    }
+}
+
+/// A value a section makes available to its body under `name`, shadowing
+/// any field of that name.
+#[derive(Clone)]
+struct Binding {
+    name: String,
+    /// A place expression reading the value.
+    value: TokenStream,
+    /// The value's type, if it is known.
+    ty: Option<Type>,
+}
+
+/// What a name in a template refers to.
+enum Resolved<'a> {
+    Field(CaseField<'a>),
+    Bound(Box<Binding>),
+}
+
+/// The state of lowering one case's template.
+struct Lower<'c, 'a> {
+    case: &'c Case<'a>,
+    greeting: &'c Greeting,
+    /// The bindings of the sections around the segments being lowered,
+    /// innermost last.
+    scope: Vec<Binding>,
+    /// The names bound by closures, `let` and `match` patterns within the
+    /// expression being rewritten, which shadow fields and bindings alike.
+    locals: Vec<String>,
+    used: Vec<Member>,
+    bounds: Vec<(Type, TokenStream)>,
+    problems: Vec<Error>,
+}
+
+impl<'a> Lower<'_, 'a> {
+    /// What `name` refers to, if it is bound by a section or names a field
+    /// that is not skipped.
+    fn resolve(&mut self, name: &str) -> Option<Resolved<'a>> {
+        if let Some(binding) = self.scope.iter().rev().find(|b| b.name == name) {
+            return Some(Resolved::Bound(Box::new(binding.clone())));
+        }
+        self.field(name).map(Resolved::Field)
+    }
+
+    /// The field templates know as `name`, if it is not skipped, whatever
+    /// sections bind.
+    fn field(&mut self, name: &str) -> Option<CaseField<'a>> {
+        let field = self.case.field_named(name)?;
+        if !self.used.contains(&field.member) {
+            self.used.push(field.member.clone());
+        }
+        Some(field)
+    }
+
+    /// Whether `name` is a skipped field that nothing shadows.
+    fn is_skipped(&self, name: &str) -> bool {
+        !self.scope.iter().any(|b| b.name == name)
+            && self.case.field_named(name).is_none()
+            && self.case.fields().any(|field| field.name == name)
+    }
+
+    /// Reports that `name` cannot be used, suggesting the closest usable
+    /// name for typos.
+    fn unusable(&mut self, name: &str, span: Span) {
+        let error = if self.is_skipped(name) {
+            skipped(name)
+        } else {
+            let usable = self
+                .scope
+                .iter()
+                .map(|b| b.name.clone())
+                .chain(
+                    self.case
+                        .fields()
+                        .filter(|field| !field.args.skip.is_present())
+                        .map(|field| field.name),
+                )
+                .collect::<Vec<_>>();
+            Error::unknown_field_with_alts(name, &usable)
+        };
+        self.problems.push(error.with_span(&span));
+    }
+
+    /// A place expression reading what a name resolved to.
+    fn value(&self, resolved: &Resolved) -> TokenStream {
+        match resolved {
+            Resolved::Field(field) => self.case.access(&field.member),
+            Resolved::Bound(binding) => binding.value.clone(),
+        }
+    }
+
+    fn segments(&mut self, segments: &[Segment]) -> TokenStream {
+        let writes = segments
+            .iter()
+            .map(|segment| match segment {
+                Segment::Text(text) => quote! {
+                    f.write_str(#text)?;
+                },
+                Segment::Placeholder(p) => self.placeholder(p),
+                Segment::IfSome { member, body, span } => self.if_some(member, body, *span),
+                Segment::If {
+                    cond,
+                    then,
+                    otherwise,
+                    span,
+                } => {
+                    let mut cond = (**cond).clone();
+                    self.visit_expr_mut(&mut cond);
+                    let then = self.segments(then);
+                    let otherwise = (!otherwise.is_empty()).then(|| {
+                        let otherwise = self.segments(otherwise);
+                        quote!(else { #otherwise })
+                    });
+                    quote_spanned! {*span=>
+                        if #cond {
+                            #then
+                        } #otherwise
+                    }
+                }
+                Segment::Each { each, body, span } => self.each(each, body, *span),
+                Segment::Plural {
+                    count,
+                    offset,
+                    exact,
+                    forms,
+                    other,
+                    span,
+                } => self.icu_plural(count, *offset, exact, forms, other, *span),
+                Segment::PluralCount(span) => {
+                    let count = self.scope.iter().rev().find(|b| b.name == "#");
+                    let count = count.expect("`#` is only parsed within plurals");
+                    if let Some(ty) = &count.ty {
+                        self.bounds.push((ty.clone(), quote!(::core::fmt::Display)));
+                    }
+                    let count = &count.value;
+                    quote_spanned! {*span=>
+                        ::core::write!(f, "{}", #count)?;
+                    }
+                }
+                Segment::Select {
+                    value,
+                    cases,
+                    other,
+                    span,
+                } => self.select(value, cases, other, *span),
+            })
+            .collect::<Vec<_>>();
+        quote!(#(#writes)*)
+    }
+
+    fn placeholder(&mut self, p: &Placeholder) -> TokenStream {
+        let (fmt, counts) = match &p.spec {
+            Some(spec) => {
+                let fmt = format!(
+                    "{{:{}}}",
+                    spec.format_string("__greet_width", "__greet_precision")
+                );
+                let (width, precision) = spec.count_fields();
+                let counts = [("__greet_width", width), ("__greet_precision", precision)]
+                    .into_iter()
+                    .filter_map(|(name, member)| {
+                        let count = member_name(member?);
+                        let Some(resolved) = self.resolve(&count) else {
+                            self.unusable(&count, p.span);
+                            return None;
+                        };
+                        let name = format_ident!("{}", name);
+                        let value = self.value(&resolved);
+                        Some(quote!(, #name = #value))
+                    })
+                    .collect::<Vec<_>>();
+                (fmt, quote!(#(#counts)*))
+            }
+            None => ("{}".to_string(), quote!()),
+        };
+
+        // The value, the options of the field it is, and its type if known
+        let (value, args, ty) = match &p.arg {
+            Argument::Field { member, path } => {
+                let name = member_name(member);
+                let Some(resolved) = self.resolve(&name) else {
+                    self.unusable(&name, p.span);
+                    return quote!();
+                };
+                let value = self.value(&resolved);
+                let value = quote!(#value #(.#path)*);
+                match resolved {
+                    Resolved::Field(field) => {
+                        let args = field.args;
+                        if !path.is_empty() && (args.with.is_some() || args.default.is_some()) {
+                            self.problems.push(
+                                Error::custom(format!(
+                                    "field `{name}` is formatted with `with` or `default` and cannot be followed by a path"
+                                ))
+                                .with_span(&p.span),
+                            );
+                        }
+                        let ty = path.is_empty().then(|| args.ty.clone());
+                        (value, Some(args), ty)
+                    }
+                    Resolved::Bound(binding) => {
+                        (value, None, binding.ty.filter(|_| path.is_empty()))
+                    }
+                }
+            }
+            Argument::Expr(expr) => {
+                let mut expr = (**expr).clone();
+                self.visit_expr_mut(&mut expr);
+                (quote!((#expr)), None, None)
+            }
+        };
+
+        let format_trait = p.format_trait();
+        let with = args.and_then(|args| args.with.as_ref());
+        let fallback = p
+            .fallback
+            .clone()
+            .or_else(|| args.and_then(|args| args.default.as_ref().map(|d| d.value())));
+        // `Option`s are unwrapped rather than failing to implement `Display`,
+        // unless they are formatted as a whole by `with` or `Debug`.
+        let inner = ty.as_ref().and_then(option_inner);
+        let unwrap =
+            fallback.is_some() || (inner.is_some() && with.is_none() && format_trait != "Debug");
+        let formatted = if unwrap { inner } else { ty.as_ref() };
+        // Filters take anything `Display`, except for `plural` taking counts.
+        let bound = match p.filters.first() {
+            None => {
+                let format_trait = format_ident!("{}", format_trait);
+                quote!(::core::fmt::#format_trait)
+            }
+            Some(filter) if filter.name == "plural" => quote!(::greet::plural::Count),
+            Some(_) => quote!(::core::fmt::Display),
+        };
+        if let (Some(ty), None) = (formatted, with) {
+            self.bounds.push((ty.clone(), bound));
+        }
+        if with.is_some() && !p.filters.is_empty() {
+            self.problems.push(
+                Error::custom("a field formatted with `with` cannot go through filters")
+                    .with_span(&p.span),
+            );
+        }
+
+        let format_with = |value: TokenStream| match with {
+            // Calling through a closure lets deref coercion turn
+            // e.g. a `&String` into the `&str` the function takes.
+            Some(with) => quote! {
+                ::greet::FormatWith::new(#value, |__greet_v, __greet_f| #with(__greet_v, __greet_f))
+            },
+            None => value,
+        };
+        if unwrap {
+            let some = self.filter(&p.filters, quote!(__greet_value), p.span);
+            let some = format_with(some);
+            let none = fallback.map(|fallback| {
+                quote! {
+                    ::core::write!(f, #fmt, #fallback #counts)?;
+                }
+            });
+            quote_spanned! {p.span=>
+                match &#value {
+                    ::core::option::Option::Some(__greet_value) => {
+                        ::core::write!(f, #fmt, #some #counts)?;
+                    }
+                    ::core::option::Option::None => {
+                        #none
+                    }
+                }
+            }
+        } else {
+            let value = self.filter(&p.filters, quote!(&#value), p.span);
+            let value = format_with(value);
+            quote! {
+                ::core::write!(f, #fmt, #value #counts)?;
+            }
+        }
+    }
+
+    /// Passes `value`, a reference, through `filters`: the built-in ones of
+    /// `greet::filters`, `plural`, and those of the greeting's `filters`
+    /// module.
+    fn filter(&mut self, filters: &[Filter], value: TokenStream, span: Span) -> TokenStream {
+        filters.iter().fold(value, |value, filter| {
+            let name = &filter.name;
+            if name == "plural" {
+                return self.plural(filter, value, span);
+            }
+            let builtin = BUILTIN_FILTERS.iter().find(|(builtin, _)| name == builtin);
+            let problem = match (builtin, &self.greeting.filters) {
+                _ if !filter.named.is_empty() => {
+                    Some(format!("`{name}` takes no named arguments: only `plural` does"))
+                }
+                (Some((_, 0)), _) if !filter.args.is_empty() => {
+                    Some(format!("`{name}` takes no arguments"))
+                }
+                (Some((_, 1)), _) if filter.args.len() != 1 => {
+                    Some(format!("`{name}` takes one argument, e.g. `{name}:40`"))
+                }
+                (None, None) => Some(format!(
+                    "unknown filter `{name}`: expected `plural`, {}, or a function of the module given by `filters = path::to::module`",
+                    BUILTIN_FILTERS
+                        .map(|(builtin, _)| format!("`{builtin}`"))
+                        .join(", ")
+                )),
+                _ => None,
+            };
+            if let Some(problem) = problem {
+                self.problems.push(Error::custom(problem).with_span(name));
+                return value;
+            }
+            let module = match (builtin, &self.greeting.filters) {
+                (None, Some(module)) => quote!(#module),
+                _ => quote!(::greet::filters),
+            };
+            let args = &filter.args;
+            quote_spanned! {name.span()=>
+                #module::#name(#value #(, #args)*)
+            }
+        })
+    }
+
+    /// `plural:"year","years"` or `plural(one = "year", other = "years")`:
+    /// the form for the count `value`, chosen by the greeting's plural rules.
+    fn plural(&mut self, filter: &Filter, value: TokenStream, span: Span) -> TokenStream {
+        let forms = match (&filter.args[..], &filter.named[..]) {
+            ([one, other], []) => vec![("one".to_string(), one), ("other".to_string(), other)],
+            ([], named) if !named.is_empty() => named
+                .iter()
+                .map(|(category, form)| (category.to_string(), form))
+                .collect(),
+            _ => {
+                self.problems.push(
+                    Error::custom(
+                        "`plural` takes the singular and plural forms, e.g. `plural:\"year\",\"years\"`, \
+                         or the forms by plural category, e.g. `plural(one = \"year\", other = \"years\")`",
+                    )
+                    .with_span(&span),
+                );
+                return value;
+            }
+        };
+
+        let mut arms = Vec::new();
+        let mut other = None;
+        for (i, (category, form)) in forms.iter().enumerate() {
+            let variant = PLURAL_CATEGORIES
+                .iter()
+                .find(|(name, _)| name == category)
+                .map(|&(_, variant)| variant);
+            let problem = match variant {
+                _ if forms[..i].iter().any(|(c, _)| c == category) => {
+                    format!("duplicate plural category `{category}`")
+                }
+                _ if !matches!(form, Lit::Str(_)) => {
+                    "plural forms must be string literals".to_string()
+                }
+                None => format!(
+                    "unknown plural category `{category}`: expected one of {}",
+                    PLURAL_CATEGORIES
+                        .map(|(name, _)| format!("`{name}`"))
+                        .join(", ")
+                ),
+                Some("Other") => {
+                    other = Some(form);
+                    continue;
+                }
+                Some(variant) => {
+                    let variant = format_ident!("{}", variant);
+                    arms.push(quote!(::greet::plural::Category::#variant => #form,));
+                    continue;
+                }
+            };
+            self.problems.push(Error::custom(problem).with_span(&span));
+        }
+        let Some(other) = other else {
+            self.problems.push(
+                Error::custom("`plural` needs an `other` form, used for every category not given")
+                    .with_span(&span),
+            );
+            return value;
+        };
+
+        let rules = self.plural_rules();
+        quote_spanned! {span=>
+            match #rules(::greet::plural::Count::count(#value)) {
+                #(#arms)*
+                _ => #other,
+            }
+        }
+    }
+
+    /// The greeting's plural rules.
+    fn plural_rules(&self) -> TokenStream {
+        let greeting = self.greeting;
+        let locale = greeting
+            .locale
+            .as_ref()
+            .or(greeting.default_locale.as_ref());
+        match (&greeting.plural_rules, locale) {
+            (Some(rules), _) => quote!(#rules),
+            (None, Some(locale)) => quote!(::greet::plural::rules_for(#locale)),
+            (None, None) => quote!(::greet::plural::english),
+        }
+    }
+
+    /// A place expression reading what an ICU argument refers to, and its
+    /// type if it is known.
+    fn operand(&mut self, arg: &Argument, span: Span) -> Option<(TokenStream, Option<Type>)> {
+        match arg {
+            Argument::Field { member, path } => {
+                let name = member_name(member);
+                let Some(resolved) = self.resolve(&name) else {
+                    self.unusable(&name, span);
+                    return None;
+                };
+                let value = respan(self.value(&resolved), span);
+                let ty = match &resolved {
+                    Resolved::Field(field) => Some(&field.args.ty),
+                    Resolved::Bound(binding) => binding.ty.as_ref(),
+                };
+                let ty = ty.filter(|_| path.is_empty()).cloned();
+                Some((quote!(#value #(.#path)*), ty))
+            }
+            Argument::Expr(expr) => {
+                let mut expr = (**expr).clone();
+                self.visit_expr_mut(&mut expr);
+                Some((quote!((#expr)), None))
+            }
+        }
+    }
+
+    /// ICU's `{count, plural, ...}`: a match on exact counts, then on the
+    /// plural category, with `#` bound to the count minus the offset.
+    fn icu_plural(
+        &mut self,
+        count: &Argument,
+        offset: u64,
+        exact: &[(u64, Vec<Segment>)],
+        forms: &[(String, Vec<Segment>)],
+        other: &[Segment],
+        span: Span,
+    ) -> TokenStream {
+        let Some((value, ty)) = self.operand(count, span) else {
+            return quote!();
+        };
+        if let Some(ty) = &ty {
+            self.bounds
+                .push((ty.clone(), quote!(::greet::plural::Count)));
+        }
+        let n = format_ident!("__greet_count{}", self.scope.len());
+        let offset_n = match offset {
+            0 => quote!(#n),
+            offset => quote!(#n.saturating_sub(#offset)),
+        };
+        self.scope.push(Binding {
+            name: "#".to_string(),
+            value: if offset == 0 {
+                quote!(#value)
+            } else {
+                offset_n.clone()
+            },
+            ty: ty.filter(|_| offset == 0),
+        });
+        let exact = exact
+            .iter()
+            .map(|(count, message)| {
+                let message = self.segments(message);
+                quote!(#count => { #message })
+            })
+            .collect::<Vec<_>>();
+        let forms = forms
+            .iter()
+            .map(|(category, message)| {
+                let (_, variant) = PLURAL_CATEGORIES
+                    .iter()
+                    .find(|(name, _)| name == category)
+                    .expect("plural categories are checked when parsing");
+                let variant = format_ident!("{}", variant);
+                let message = self.segments(message);
+                quote!(::greet::plural::Category::#variant => { #message })
+            })
+            .collect::<Vec<_>>();
+        let other = self.segments(other);
+        self.scope.pop();
+
+        let rules = self.plural_rules();
+        let by_category = if forms.is_empty() {
+            quote!({ #other })
+        } else {
+            quote! {
+                match #rules(#offset_n) {
+                    #(#forms)*
+                    _ => { #other }
+                }
+            }
+        };
+        let body = if exact.is_empty() {
+            by_category
+        } else {
+            quote! {
+                match #n {
+                    #(#exact)*
+                    _ => #by_category
+                }
+            }
+        };
+        quote_spanned! {span=>
+            let #n = ::greet::plural::Count::count(&#value);
+            #body
+        }
+    }
+
+    /// ICU's `{value, select, ...}`: a match on the value as a `&str`.
+    fn select(
+        &mut self,
+        value: &Argument,
+        cases: &[(String, Vec<Segment>)],
+        other: &[Segment],
+        span: Span,
+    ) -> TokenStream {
+        let Some((value, ty)) = self.operand(value, span) else {
+            return quote!();
+        };
+        if let Some(ty) = ty {
+            self.bounds.push((ty, quote!(::core::convert::AsRef<str>)));
+        }
+        let cases = cases
+            .iter()
+            .map(|(case, message)| {
+                let message = self.segments(message);
+                quote!(#case => { #message })
+            })
+            .collect::<Vec<_>>();
+        let other = self.segments(other);
+        quote_spanned! {span=>
+            match ::core::convert::AsRef::<str>::as_ref(&#value) {
+                #(#cases)*
+                _ => { #other }
+            }
+        }
+    }
+
+    /// `{?name}...{/name}`: the body, with `name` bound to the value inside
+    /// the `Option`, if there is one.
+    fn if_some(&mut self, member: &Member, body: &[Segment], span: Span) -> TokenStream {
+        let name = member_name(member);
+        let Some(resolved) = self.resolve(&name) else {
+            self.unusable(&name, span);
+            return quote!();
+        };
+        let value = self.value(&resolved);
+        let ty = match &resolved {
+            Resolved::Field(field) => Some(&field.args.ty),
+            Resolved::Bound(binding) => binding.ty.as_ref(),
+        };
+        let inner = format_ident!("__greet_some{}", self.scope.len());
+        self.scope.push(Binding {
+            name,
+            value: quote!((*#inner)),
+            ty: ty.and_then(option_inner).cloned(),
+        });
+        let body = self.segments(body);
+        self.scope.pop();
+        quote_spanned! {span=>
+            if let ::core::option::Option::Some(#inner) = &#value {
+                #body
+            }
+        }
+    }
+
+    /// `{#each tags}...{/each}`: a loop writing the body, with the item
+    /// bound, and the separators between items.
+    fn each(&mut self, each: &Each, body: &[Segment], span: Span) -> TokenStream {
+        let depth = self.scope.len();
+        let item = format_ident!("__greet_item{}", depth);
+        let iter = format_ident!("__greet_iter{}", depth);
+        // The iterator, the item's place and its type if known
+        let (items, value, ty) = match &each.collection {
+            Argument::Field { member, path } => {
+                let name = member_name(member);
+                let Some(resolved) = self.resolve(&name) else {
+                    self.unusable(&name, span);
+                    return quote!();
+                };
+                let ty = match &resolved {
+                    Resolved::Field(field) => Some(&field.args.ty),
+                    Resolved::Bound(binding) => binding.ty.as_ref(),
+                }
+                .filter(|_| path.is_empty())
+                .and_then(item_type)
+                .cloned();
+                let collection = respan(self.value(&resolved), span);
+                // A method call, so that references to collections are
+                // dereferenced until one iterates by reference.
+                (
+                    quote!((&#collection #(.#path)*).into_iter()),
+                    quote!((*#item)),
+                    ty,
+                )
+            }
+            Argument::Expr(expr) => {
+                let mut expr = (**expr).clone();
+                self.visit_expr_mut(&mut expr);
+                (
+                    quote!(::core::iter::IntoIterator::into_iter(#expr)),
+                    quote!(#item),
+                    None,
+                )
+            }
+        };
+
+        self.scope.push(Binding {
+            name: each.item.clone(),
+            value,
+            ty,
+        });
+        let body = self.segments(body);
+        self.scope.pop();
+        match (&each.sep, &each.last) {
+            (None, None) => quote! {
+                for #item in #items {
+                    #body
+                }
+            },
+            (sep, last) => {
+                let sep = sep.as_deref().unwrap_or_default();
+                let last = last.as_deref().unwrap_or(sep);
+                let first = format_ident!("__greet_first{}", depth);
+                quote! {
+                    let mut #iter = ::core::iter::Iterator::peekable(#items);
+                    let mut #first = true;
+                    while let ::core::option::Option::Some(#item) = #iter.next() {
+                        if !#first {
+                            f.write_str(if #iter.peek().is_some() { #sep } else { #last })?;
+                        }
+                        #first = false;
+                        #body
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl Lower<'_, '_> {
+    /// Visits `visit` with the names `pats` bind in scope.
+    fn with_locals<'p>(
+        &mut self,
+        pats: impl IntoIterator<Item = &'p Pat>,
+        visit: impl FnOnce(&mut Self),
+    ) {
+        let outer = self.locals.len();
+        for pat in pats {
+            PatBindings(&mut self.locals).visit_pat(pat);
+        }
+        visit(self);
+        self.locals.truncate(outer);
+    }
+
+    fn is_local(&self, name: &Ident) -> bool {
+        let name = name.unraw().to_string();
+        self.locals.contains(&name)
+    }
+}
+
+/// Rewrites placeholder expressions to read the names and `self` fields
+/// they use and to call plain function names as methods of `self`. Names
+/// bound within the expression are left alone, as are calls of capitalized
+/// names such as `Some(3)`, which construct values.
+impl VisitMut for Lower<'_, '_> {
+    fn visit_expr_mut(&mut self, expr: &mut Expr) {
+        match expr {
+            Expr::Call(call) if plain_name(&call.func).is_some() => {
+                for arg in &mut call.args {
+                    self.visit_expr_mut(arg);
+                }
+                let callee = plain_name(&call.func).cloned();
+                if let Some(method) =
+                    callee.filter(|name| !self.is_local(name) && !is_constructor(name))
+                {
+                    let args = &call.args;
+                    *expr = parse_quote_spanned!(method.span()=> self.#method(#args));
+                }
+                return;
+            }
+            // Patterns are not visited: they bind names rather than read them.
+            Expr::Closure(closure) => {
+                let body = &mut closure.body;
+                self.with_locals(&closure.inputs, |lower| lower.visit_expr_mut(body));
+                return;
+            }
+            Expr::Match(m) => {
+                self.visit_expr_mut(&mut m.expr);
+                for arm in &mut m.arms {
+                    let (guard, body) = (&mut arm.guard, &mut arm.body);
+                    self.with_locals([&arm.pat], |lower| {
+                        if let Some((_, guard)) = guard {
+                            lower.visit_expr_mut(guard);
+                        }
+                        lower.visit_expr_mut(body);
+                    });
+                }
+                return;
+            }
+            // `if let` binds names for its `then` branch only.
+            Expr::If(e) => {
+                let pat = match &mut *e.cond {
+                    Expr::Let(cond) => {
+                        self.visit_expr_mut(&mut cond.expr);
+                        Some(&*cond.pat)
+                    }
+                    cond => {
+                        self.visit_expr_mut(cond);
+                        None
+                    }
+                };
+                let then = &mut e.then_branch;
+                self.with_locals(pat, |lower| lower.visit_block_mut(then));
+                if let Some((_, otherwise)) = &mut e.else_branch {
+                    self.visit_expr_mut(otherwise);
+                }
+                return;
+            }
+            Expr::Let(e) => {
+                self.visit_expr_mut(&mut e.expr);
+                return;
+            }
+            // `self.name` always reads a field, which must be usable under
+            // the name templates know it by.
+            Expr::Field(e) if plain_name(&e.base).is_some_and(|base| base == "self") => {
+                let span = e.member.span();
+                let name = member_name(&e.member);
+                match self.field(&name) {
+                    Some(field) => {
+                        let value = respan(self.case.access(&field.member), span);
+                        *expr = parse_quote!(#value);
+                    }
+                    None => self.unusable(&name, span),
+                }
+                return;
+            }
+            _ => {}
+        }
+        let Some(name) = plain_name(expr).filter(|name| !self.is_local(name)) else {
+            visit_mut::visit_expr_mut(self, expr);
+            return;
+        };
+        // Other names may be constants or locals rather than fields, but
+        // skipped fields are off limits.
+        let span = name.span();
+        let name = name.unraw().to_string();
+        match self.resolve(&name) {
+            Some(resolved) => {
+                // Spanned so that rustc reports type errors against the
+                // template.
+                let value = respan(self.value(&resolved), span);
+                *expr = parse_quote!(#value);
+            }
+            None if self.is_skipped(&name) => self.problems.push(skipped(&name).with_span(&span)),
+            None => {}
+        }
+    }
+
+    fn visit_block_mut(&mut self, block: &mut Block) {
+        let outer = self.locals.len();
+        for stmt in &mut block.stmts {
+            match stmt {
+                Stmt::Local(local) => {
+                    if let Some(init) = &mut local.init {
+                        self.visit_expr_mut(&mut init.expr);
+                        if let Some((_, diverge)) = &mut init.diverge {
+                            self.visit_expr_mut(diverge);
+                        }
+                    }
+                    PatBindings(&mut self.locals).visit_pat(&local.pat);
+                }
+                stmt => self.visit_stmt_mut(stmt),
+            }
+        }
+        self.locals.truncate(outer);
+    }
+}
+
+/// Collects the names a pattern binds.
+struct PatBindings<'n>(&'n mut Vec<String>);
+
+impl<'ast> Visit<'ast> for PatBindings<'_> {
+    fn visit_pat_ident(&mut self, pat: &'ast PatIdent) {
+        self.0.push(pat.ident.unraw().to_string());
+        visit::visit_pat_ident(self, pat);
+    }
+}
+
+/// Whether `name` is capitalized, as the names of tuple structs and enum
+/// variants are.
+fn is_constructor(name: &Ident) -> bool {
+    name.unraw().to_string().starts_with(char::is_uppercase)
+}
+
+/// The filters of `greet::filters`, with the number of arguments each takes.
+const BUILTIN_FILTERS: [(&str, usize); 6] = [
+    ("upper", 0),
+    ("lower", 0),
+    ("capitalize", 0),
+    ("title_case", 0),
+    ("trim", 0),
+    ("truncate", 1),
+];
+
+/// The error for a template using skipped field `name`.
+fn skipped(name: &str) -> Error {
+    Error::custom(format!(
+        "field `{name}` is marked `skip` and cannot be used in the template"
+    ))
+}
@@ -0,0 +1,129 @@
+//! Templates read from a TOML file, for `#[greet2(path = "...", key = "...")]`.
+//!
+//! The file is read relative to the manifest directory of the crate being
+//! compiled, and embedded with `include_str!` so that editing it rebuilds
+//! the crate. Errors in a template read from a file say where it is, since
+//! their span can only point at the `key`.
+
+use std::path::PathBuf;
+
+use darling::Error;
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::LitStr;
+use toml_edit::ImDocument;
+
+/// Where a template read from a file starts.
+#[derive(Clone, Debug)]
+pub(crate) struct Source {
+    /// The path as given in `path`.
+    path: String,
+    line: usize,
+    column: usize,
+}
+
+/// Adds where the template is to every error in `error`, if it was read
+/// from a file.
+pub(crate) fn annotate(source: Option<&Source>, error: Error) -> Error {
+    let Some(source) = source else {
+        return error;
+    };
+    let errors = error
+        .into_iter()
+        .map(|error| {
+            let span = error.span();
+            Error::custom(format!(
+                "{error}, in the template at {}:{}:{}",
+                source.path, source.line, source.column
+            ))
+            .with_span(&span)
+        })
+        .collect();
+    Error::multiple(errors)
+}
+
+/// A parsed file of templates.
+#[derive(Debug)]
+pub(crate) struct TemplateFile {
+    /// The `path` option.
+    path: LitStr,
+    absolute: PathBuf,
+    document: ImDocument<String>,
+}
+
+impl TemplateFile {
+    /// Reads and parses the file at `path`.
+    pub(crate) fn open(path: LitStr) -> darling::Result<Self> {
+        let manifest_dir = std::env::var_os("CARGO_MANIFEST_DIR").ok_or_else(|| {
+            Error::custom("`path` needs `CARGO_MANIFEST_DIR`, which cargo sets").with_span(&path)
+        })?;
+        let absolute = PathBuf::from(manifest_dir).join(path.value());
+        let text = std::fs::read_to_string(&absolute).map_err(|error| {
+            Error::custom(format!("cannot read `{}`: {error}", absolute.display())).with_span(&path)
+        })?;
+        let document = ImDocument::parse(text.clone()).map_err(|error| {
+            let (line, column) = error
+                .span()
+                .map_or((1, 1), |span| line_column(&text, span.start));
+            Error::custom(format!(
+                "invalid TOML in {}:{line}:{column}: {}",
+                path.value(),
+                error.message().trim_end()
+            ))
+            .with_span(&path)
+        })?;
+        Ok(Self {
+            path,
+            absolute,
+            document,
+        })
+    }
+
+    /// The template at `key`, a dotted path such as `person.welcome`. The
+    /// returned literal carries the span of `key`.
+    pub(crate) fn template(&self, key: &LitStr) -> darling::Result<(LitStr, Source)> {
+        let path = self.path.value();
+        let value = key.value();
+        let mut item = self.document.as_item();
+        for part in value.split('.') {
+            item = item.get(part).ok_or_else(|| {
+                Error::custom(format!("no key `{value}` in `{path}`")).with_span(key)
+            })?;
+        }
+        let (line, column) = item
+            .span()
+            .map_or((1, 1), |span| line_column(self.document.raw(), span.start));
+        let source = Source { path, line, column };
+        match item.as_str() {
+            Some(template) => Ok((LitStr::new(template, key.span()), source)),
+            None => Err(Error::custom(format!(
+                "`{value}` at {}:{line}:{column} is not a string",
+                source.path
+            ))
+            .with_span(key)),
+        }
+    }
+
+    /// An item that makes the compiler read the file, so that changing it
+    /// rebuilds the crate.
+    pub(crate) fn track(&self) -> TokenStream {
+        let absolute = self.absolute.to_string_lossy();
+        quote! {
+            const _: &str = ::core::include_str!(#absolute);
+        }
+    }
+}
+
+/// The 1-based line and column of byte `offset` in `text`.
+fn line_column(text: &str, offset: usize) -> (usize, usize) {
+    let before = &text[..offset.min(text.len())];
+    let line = before.matches('\n').count() + 1;
+    let column = before
+        .rsplit('\n')
+        .next()
+        .unwrap_or_default()
+        .chars()
+        .count()
+        + 1;
+    (line, column)
+}
@@ -0,0 +1,170 @@
+//! Runtime tests of the greetings the macros generate: what `greeting()`,
+//! `write_greeting` and `write_greeting_io` write, and `Display`.
+
+use std::fmt::Debug;
+
+use derive::{add_greet, greet, Greet, Greet2};
+use greet::Greet;
+
+add_greet!(
+    struct Person {
+        name: String,
+        age: u32,
+    }
+);
+
+#[derive(Greet)]
+#[greet(content = "{1}, {0} years old")]
+struct Age(u32, &'static str);
+
+#[greet(content = "Point at ({0}, {1:.1})")]
+struct Point(i32, f64);
+
+#[derive(Greet)]
+struct HelloWorld;
+
+#[derive(Greet)]
+#[greet(content = "{name} the robot, version {version}.", display)]
+struct Robot {
+    name: &'static str,
+    version: f32,
+}
+
+#[derive(Greet2)]
+#[greet2(display)]
+enum Visitor {
+    #[greet2(content = "Welcome back, {0}!")]
+    Member(String),
+    #[greet2(content = "Hello, guest #{id}.")]
+    Guest {
+        id: u32,
+    },
+    GoodMorning,
+}
+
+#[greet(content = "Status: {code}")]
+enum Status {
+    Ok {
+        code: u16,
+    },
+    #[greet(content = "Status unknown")]
+    Unknown,
+}
+
+add_greet!(
+    #[greet(content = "{name} has {count} item(s).")]
+    struct Basket<'a, T>
+    where
+        T: Debug,
+    {
+        name: &'a str,
+        count: usize,
+        items: Vec<T>,
+    }
+);
+
+#[derive(Greet2)]
+#[greet2(content = "Debugging {value:?}")]
+struct Inspect<V: Clone> {
+    value: V,
+}
+
+fn person() -> Person {
+    Person {
+        name: "Hieu".to_string(),
+        age: 24,
+    }
+}
+
+#[test]
+fn greeting() {
+    assert_eq!(
+        person().greeting(),
+        "Hello, my name is Hieu and I am 24 years old."
+    );
+}
+
+#[test]
+fn write_greeting_into_a_string() {
+    let mut out = String::from("> ");
+    person().write_greeting(&mut out).unwrap();
+    assert_eq!(out, "> Hello, my name is Hieu and I am 24 years old.");
+}
+
+#[test]
+fn write_greeting_io_into_bytes() {
+    let mut out = Vec::new();
+    person().write_greeting_io(&mut out).unwrap();
+    assert_eq!(out, b"Hello, my name is Hieu and I am 24 years old.");
+}
+
+#[test]
+fn display() {
+    let robot = Robot {
+        name: "Bender",
+        version: 2.5,
+    };
+    assert_eq!(robot.to_string(), "Bender the robot, version 2.5.");
+    assert_eq!(robot.to_string(), robot.greeting());
+    assert_eq!(
+        format!("[{robot:^34}]"),
+        "[  Bender the robot, version 2.5.  ]"
+    );
+    assert_eq!(format!("[{robot:>10.6}]"), "[    Bender]");
+    assert_eq!(format!("{:<4}|", Visitor::GoodMorning), "Good morning|");
+}
+
+#[test]
+fn enums() {
+    assert_eq!(
+        Visitor::Member("Minh".to_string()).greeting(),
+        "Welcome back, Minh!"
+    );
+    assert_eq!(Visitor::Guest { id: 7 }.greeting(), "Hello, guest #7.");
+    assert_eq!(Visitor::GoodMorning.greeting(), "Good morning");
+    assert_eq!(Status::Ok { code: 200 }.greeting(), "Status: 200");
+    assert_eq!(Status::Unknown.greeting(), "Status unknown");
+}
+
+#[test]
+fn tuple_and_unit_structs() {
+    assert_eq!(Age(24, "Hieu").greeting(), "Hieu, 24 years old");
+    assert_eq!(Point(3, 4.25).greeting(), "Point at (3, 4.2)");
+    assert_eq!(HelloWorld.greeting(), "Hello world");
+}
+
+#[test]
+fn generics() {
+    let basket = Basket {
+        name: "Fruits",
+        count: 2,
+        items: vec!["apple", "pear"],
+    };
+    assert_eq!(basket.greeting(), "Fruits has 2 item(s).");
+    assert_eq!(basket.items.len(), 2);
+    assert_eq!(
+        Inspect {
+            value: vec![1, 2, 3]
+        }
+        .greeting(),
+        "Debugging [1, 2, 3]"
+    );
+}
+
+#[test]
+fn trait_objects() {
+    let greeters: Vec<Box<dyn Greet>> = vec![
+        Box::new(person()),
+        Box::new(HelloWorld),
+        Box::new(Visitor::GoodMorning),
+    ];
+    let greetings = greeters.iter().map(|g| g.greeting()).collect::<Vec<_>>();
+    assert_eq!(
+        greetings,
+        [
+            "Hello, my name is Hieu and I am 24 years old.",
+            "Hello world",
+            "Good morning",
+        ]
+    );
+}
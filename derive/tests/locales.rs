@@ -0,0 +1,105 @@
+//! Runtime tests of localized greetings: which template `greeting_in`
+//! picks for a locale, and the plural rules it counts with.
+
+use derive::Greet2;
+use greet::Greet;
+
+#[derive(Greet2)]
+#[greet2(
+    default_locale = "en",
+    content = "Hi, I'm {name}. I have {cats} {cats|plural:\"cat\",\"cats\"}."
+)]
+#[greet2(
+    locale = "vi",
+    content = "Xin chào, tôi là {name}. Tôi có {cats} con mèo."
+)]
+#[greet2(
+    locale = "vi-VN",
+    content = "Chào bạn, mình là {name}, có {cats} con mèo."
+)]
+#[greet2(
+    locale = "fr",
+    content = "Je suis {name}. J'ai {cats} {cats|plural:\"chat\",\"chats\"}."
+)]
+#[greet2(
+    locale = "pt-BR",
+    content = "Eu sou {name}. Tenho {cats} {cats|plural:\"gato\",\"gatos\"}."
+)]
+#[greet2(fallback = "pt-PT > pt-BR")]
+#[greet2(fallback = "es-MX > es-419 > fr")]
+struct Person {
+    name: &'static str,
+    cats: u32,
+}
+
+// Named greetings are translated per variant, as in `use_locales`
+#[derive(Greet2)]
+#[greet2(default_locale = "en")]
+#[greet2(name = "farewell", content = "Bye!")]
+enum Parting {
+    #[greet2(name = "farewell", locale = "vi", content = "Tạm biệt!")]
+    Friend,
+}
+
+const LAN: Person = Person {
+    name: "Lan",
+    cats: 0,
+};
+
+#[test]
+fn exact_locales() {
+    assert_eq!(
+        LAN.greeting_in("vi"),
+        "Xin chào, tôi là Lan. Tôi có 0 con mèo."
+    );
+    assert_eq!(
+        LAN.greeting_in("vi-VN"),
+        "Chào bạn, mình là Lan, có 0 con mèo."
+    );
+    assert_eq!(LAN.greeting_in("fr"), "Je suis Lan. J'ai 0 chat.");
+    // Case and separators do not matter
+    assert_eq!(
+        LAN.greeting_in("VI_vn"),
+        "Chào bạn, mình là Lan, có 0 con mèo."
+    );
+}
+
+#[test]
+fn parent_then_default_locale() {
+    // `vi-VN-x-test` → `vi-VN`, `fr-CA` → `fr`, then the default
+    assert_eq!(
+        LAN.greeting_in("vi-VN-x-test"),
+        "Chào bạn, mình là Lan, có 0 con mèo."
+    );
+    assert_eq!(LAN.greeting_in("fr-CA"), "Je suis Lan. J'ai 0 chat.");
+    assert_eq!(LAN.greeting_in("de-DE"), "Hi, I'm Lan. I have 0 cats.");
+    assert_eq!(LAN.greeting_in("en"), LAN.greeting());
+    assert_eq!(LAN.greeting_in(""), LAN.greeting());
+}
+
+#[test]
+fn fallback_chains() {
+    assert_eq!(LAN.greeting_in("pt-PT"), "Eu sou Lan. Tenho 0 gato.");
+    assert_eq!(LAN.greeting_in("es-MX"), "Je suis Lan. J'ai 0 chat.");
+    // `pt` itself has no template nor chain
+    assert_eq!(LAN.greeting_in("pt"), LAN.greeting());
+}
+
+#[test]
+fn plural_rules_of_the_locale() {
+    let person = |cats| Person { name: "An", cats };
+    assert_eq!(person(1).greeting_in("en"), "Hi, I'm An. I have 1 cat.");
+    assert_eq!(person(0).greeting_in("en"), "Hi, I'm An. I have 0 cats.");
+    assert_eq!(person(1).greeting_in("fr"), "Je suis An. J'ai 1 chat.");
+    assert_eq!(person(2).greeting_in("fr-CA"), "Je suis An. J'ai 2 chats.");
+}
+
+#[test]
+fn named_greetings() {
+    assert_eq!(Parting::Friend.farewell_string_in("vi-VN"), "Tạm biệt!");
+    assert_eq!(Parting::Friend.farewell_string_in("fr"), "Bye!");
+
+    let mut out = String::new();
+    LAN.write_greeting_in("vi", &mut out).unwrap();
+    assert_eq!(out, "Xin chào, tôi là Lan. Tôi có 0 con mèo.");
+}
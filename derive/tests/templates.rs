@@ -0,0 +1,202 @@
+//! Runtime tests of what template features write: field options, paths,
+//! expressions, sections, loops, filters, plurals and ICU MessageFormat.
+
+use std::fmt;
+
+use derive::{greet, Greet2};
+use greet::Greet;
+
+fn years(value: &u32, f: &mut fmt::Formatter) -> fmt::Result {
+    match value {
+        1 => write!(f, "1 year"),
+        n => write!(f, "{n} years"),
+    }
+}
+
+#[derive(Greet2)]
+#[greet2(content = "{full_name}, {age} old. Call me {nickname}.")]
+#[greet2(name = "farewell", content = "Bye, {full_name}!")]
+struct Person {
+    #[greet(rename = "full_name")]
+    name: String,
+    #[greet(with = years)]
+    age: u32,
+    #[greet(default = "whatever you like")]
+    nickname: Option<String>,
+    #[greet(skip)]
+    #[allow(dead_code)]
+    password: String,
+}
+
+fn person(age: u32, nickname: Option<&str>) -> Person {
+    Person {
+        name: "Hieu".to_string(),
+        age,
+        nickname: nickname.map(str::to_string),
+        password: "secret".to_string(),
+    }
+}
+
+#[test]
+fn field_options_and_named_greetings() {
+    assert_eq!(
+        person(1, None).greeting(),
+        "Hieu, 1 year old. Call me whatever you like."
+    );
+    assert_eq!(
+        person(24, Some("Hi")).greeting(),
+        "Hieu, 24 years old. Call me Hi."
+    );
+    assert_eq!(person(24, None).farewell_string(), "Bye, Hieu!");
+}
+
+struct Address {
+    city: &'static str,
+}
+
+#[greet(content = "{self.name} from {address.city}: {name.len() + 1} {self.name.to_uppercase()}")]
+struct Traveller {
+    name: &'static str,
+    address: Address,
+}
+
+#[test]
+fn paths_and_expressions() {
+    let traveller = Traveller {
+        name: "Lan",
+        address: Address { city: "Hue" },
+    };
+    assert_eq!(traveller.greeting(), "Lan from Hue: 4 LAN");
+}
+
+#[derive(Greet2)]
+enum Shape {
+    #[greet2(content = "{self.name} of side {self.side * 2.0}")]
+    Square {
+        #[greet(rename = "name")]
+        label: &'static str,
+        side: f64,
+    },
+}
+
+#[test]
+fn self_fields_in_variants() {
+    let square = Shape::Square {
+        label: "Tile",
+        side: 1.5,
+    };
+    assert_eq!(square.greeting(), "Tile of side 3");
+}
+
+#[derive(Greet2)]
+#[greet2(
+    content = "[{name:<6}|{score:>6.2}|{id:#06x}|{label:*^width$}]{?note} ({note}){/note}{if score >= 50.0} pass{else} fail{/if}"
+)]
+struct Score {
+    name: &'static str,
+    score: f64,
+    id: u32,
+    label: &'static str,
+    width: usize,
+    note: Option<&'static str>,
+}
+
+#[test]
+fn format_specs_and_sections() {
+    let score = Score {
+        name: "Minh",
+        score: 72.456,
+        id: 42,
+        label: "ok",
+        width: 6,
+        note: Some("late"),
+    };
+    assert_eq!(
+        score.greeting(),
+        "[Minh  | 72.46|0x002a|**ok**] (late) pass"
+    );
+    let score = Score {
+        score: 12.0,
+        note: None,
+        ..score
+    };
+    assert_eq!(score.greeting(), "[Minh  | 12.00|0x002a|**ok**] fail");
+}
+
+struct Member {
+    name: &'static str,
+}
+
+#[derive(Greet2)]
+#[greet2(
+    content = "{#each member in members sep=\", \" last=\" and \"}{member.name}{/each}; {#each scores.iter().rev() sep=\">\"}{it}{/each}; {#each tags list}#{it}{/each}"
+)]
+struct Team {
+    members: Vec<Member>,
+    scores: Vec<u32>,
+    tags: Vec<&'static str>,
+}
+
+#[test]
+fn loops() {
+    let team = Team {
+        members: ["An", "Binh", "Chi"].map(|name| Member { name }).into(),
+        scores: vec![1, 2, 3],
+        tags: vec!["rust", "macros"],
+    };
+    assert_eq!(
+        team.greeting(),
+        "An, Binh and Chi; 3>2>1; #rust and #macros"
+    );
+}
+
+#[derive(Greet2)]
+#[greet2(
+    content = "{name|trim|title_case} {role|upper} {bio|truncate:8} {pets} {pets|plural:\"pet\",\"pets\"}"
+)]
+struct Profile {
+    name: &'static str,
+    role: &'static str,
+    bio: &'static str,
+    pets: u32,
+}
+
+#[test]
+fn filters_and_plurals() {
+    let profile = Profile {
+        name: "  hIEU nguyen ",
+        role: "dev",
+        bio: "Writes macros for fun.",
+        pets: 1,
+    };
+    assert_eq!(profile.greeting(), "Hieu Nguyen DEV Writes m… 1 pet");
+    let profile = Profile { pets: 3, ..profile };
+    assert!(profile.greeting().ends_with("3 pets"));
+}
+
+#[derive(Greet2)]
+#[greet2(
+    syntax = "icu",
+    content = "{guests, plural, offset:1 =0 {Nobody} =1 {Only {name}} one {{name} and # other} other {{name} and # others}}, {gender, select, female {she} other {they}}."
+)]
+struct Party {
+    name: &'static str,
+    guests: u32,
+    gender: &'static str,
+}
+
+#[test]
+fn icu_messages() {
+    let party = |guests, gender| {
+        Party {
+            name: "An",
+            guests,
+            gender,
+        }
+        .greeting()
+    };
+    assert_eq!(party(0, "female"), "Nobody, she.");
+    assert_eq!(party(1, "male"), "Only An, they.");
+    assert_eq!(party(2, "female"), "An and 1 other, she.");
+    assert_eq!(party(5, "other"), "An and 4 others, they.");
+}
@@ -0,0 +1,9 @@
+//! Compile-fail tests of the diagnostics the macros report. Each file in
+//! `tests/ui` is compiled and its errors compared with the `.stderr` file
+//! next to it; run with `TRYBUILD=overwrite` to update them.
+
+#[test]
+fn ui() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/*.rs");
+}
@@ -0,0 +1,48 @@
+use derive::{greet, Greet, Greet2};
+
+#[derive(Greet2)]
+#[greet2(contents = "Hi, I am {name}.")]
+struct Unknown {
+    name: String,
+}
+
+#[derive(Greet)]
+#[greet(content = 42)]
+struct NotAString {
+    name: String,
+}
+
+#[greet(content)]
+struct NoValue {
+    name: String,
+}
+
+#[greet(content = "{name}", content = "{name}!")]
+struct Duplicate {
+    name: String,
+}
+
+#[derive(Greet2)]
+#[greet2(content = "{name}")]
+struct UnknownFieldOption {
+    #[greet2(hide)]
+    name: String,
+}
+
+#[derive(Greet2)]
+#[greet(content = "Hi, I am {name}.")]
+struct GreetOnGreet2 {
+    // Fields share `#[greet(...)]` with the other macros
+    #[greet(rename = "nickname")]
+    name: String,
+}
+
+#[derive(Greet2)]
+enum GreetOnGreet2Variant {
+    #[greet(content = "I am so happy")]
+    Happy,
+    #[greet(display)]
+    Sad,
+}
+
+fn main() {}
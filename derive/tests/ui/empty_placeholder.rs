@@ -0,0 +1,15 @@
+use derive::Greet2;
+
+#[derive(Greet2)]
+#[greet2(content = "Hi, I am {}.")]
+struct Empty {
+    name: String,
+}
+
+#[derive(Greet2)]
+#[greet2(content = "Hi, I am {:>8}.")]
+struct SpecOnly {
+    name: String,
+}
+
+fn main() {}
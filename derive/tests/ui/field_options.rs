@@ -0,0 +1,57 @@
+use derive::{greet, Greet2};
+
+#[derive(Greet2)]
+#[greet2(content = "Hi, I am {name}, my password is {password}.")]
+struct Skipped {
+    name: String,
+    #[greet2(skip)]
+    password: String,
+}
+
+// A renamed field is only known by its new name
+#[derive(Greet2)]
+#[greet2(content = "{first_name}")]
+struct Renamed {
+    #[greet2(rename = "first")]
+    first_name: String,
+}
+
+#[derive(Greet2)]
+#[greet2(content = "{last_name}")]
+struct InvalidRename {
+    #[greet2(rename = "last name")]
+    last_name: String,
+}
+
+#[greet(content = "{nickname}")]
+struct DefaultNotOption {
+    #[greet(default = "friend")]
+    nickname: String,
+}
+
+fn shout(value: &Address, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+    write!(f, "{}!", value.city)
+}
+
+struct Address {
+    city: String,
+}
+
+#[derive(Greet2)]
+#[greet2(content = "I live in {address.city}")]
+struct WithAndPath {
+    #[greet2(with = shout)]
+    address: Address,
+}
+
+// Expressions reach fields through `self` under the same rules
+#[derive(Greet2)]
+#[greet2(content = "len {self.secret.len()}{if self.secret == \"x\"}!{/if} {self.first_name}")]
+struct SkippedInExpressions {
+    #[greet2(skip)]
+    secret: String,
+    #[greet2(rename = "first")]
+    first_name: String,
+}
+
+fn main() {}
@@ -0,0 +1,51 @@
+use derive::Greet2;
+
+#[derive(Greet2)]
+#[greet2(content = "Hi, I am {name}.")]
+#[greet2(locale = "vi", content = "Xin chào, tôi {age} tuổi.")]
+struct DifferentFields {
+    name: String,
+    age: u32,
+}
+
+#[derive(Greet2)]
+#[greet2(content = "Hi, I am {name}.")]
+#[greet2(locale = "not a locale", content = "Xin chào, tôi là {name}.")]
+struct InvalidLocale {
+    name: String,
+}
+
+#[derive(Greet2)]
+#[greet2(content = "Hi, I am {name}.")]
+#[greet2(locale = "vi", content = "Xin chào, tôi là {name}.", method = "chao")]
+struct PerLocaleMethod {
+    name: String,
+}
+
+#[derive(Greet2)]
+#[greet2(content = "Hi, I am {name}.")]
+#[greet2(locale = "vi", content = "Xin chào, tôi là {name}.")]
+#[greet2(locale = "vi_VN", content = "Chào, tôi là {name}.")]
+#[greet2(locale = "VI-vn", content = "Chào bạn, tôi là {name}.")]
+struct DuplicateTranslation {
+    name: String,
+}
+
+#[derive(Greet2)]
+#[greet2(default_locale = "en", content = "Hi, I am {name}.")]
+#[greet2(locale = "en", content = "Hello, I am {name}.")]
+struct DefaultLocaleTranslated {
+    name: String,
+}
+
+#[derive(Greet2)]
+#[greet2(content = "Hi, I am {name}.")]
+#[greet2(fallback = "pt-PT")]
+#[greet2(fallback = "vi > > en")]
+#[greet2(fallback = "de > en")]
+#[greet2(fallback = "de > fr")]
+struct InvalidFallbacks {
+    name: String,
+}
+
+fn main() {}
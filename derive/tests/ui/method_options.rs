@@ -0,0 +1,32 @@
+use derive::{greet, Greet2};
+
+#[derive(Greet2)]
+#[greet2(content = "Hi, {name}!")]
+#[greet2(name = "hello", content = "Hello, {name}!", method = "greet")]
+struct ClashWithGreet {
+    name: String,
+}
+
+#[derive(Greet2)]
+#[greet2(name = "hello", content = "Hello, {name}!", method = "welcome")]
+#[greet2(name = "hi", content = "Hi, {name}!", method = "welcome")]
+struct ClashWithEachOther {
+    name: String,
+}
+
+#[greet(content = "Hi, {name}!", vis = "pub(crate)")]
+struct VisWithoutMethods {
+    name: String,
+}
+
+#[greet(content = "Hi, {name}!", method = "say hi")]
+struct InvalidMethod {
+    name: String,
+}
+
+#[greet(content = "Hi, {name}!", method = "hello", vis = "pub(nowhere)")]
+struct InvalidVis {
+    name: String,
+}
+
+fn main() {}
@@ -0,0 +1,24 @@
+use derive::{Greet, Greet2};
+
+// Only structs with a `name` and an `age`, and unit structs and variants,
+// have a default greeting
+#[derive(Greet)]
+struct Point {
+    x: i32,
+    y: i32,
+}
+
+#[derive(Greet2)]
+enum Shape {
+    Circle { radius: f64 },
+    Square(f64),
+    Empty,
+}
+
+#[derive(Greet2)]
+#[greet2(name = "farewell")]
+struct Named {
+    name: String,
+}
+
+fn main() {}
@@ -0,0 +1,49 @@
+use derive::Greet2;
+
+#[derive(Greet2)]
+#[greet2(content = "Hi, {name}!")]
+#[greet2(name = "farewell", content = "Bye, {name}!")]
+#[greet2(name = "farewell", content = "See you, {name}!")]
+struct DuplicateContent {
+    name: String,
+}
+
+#[derive(Greet2)]
+#[greet2(name = "good bye", content = "Bye, {name}!")]
+struct InvalidName {
+    name: String,
+}
+
+#[derive(Greet2)]
+#[greet2(content = "Hi, {name}!", display)]
+#[greet2(name = "farewell", content = "Bye, {name}!", display)]
+struct TwoDisplays {
+    name: String,
+}
+
+#[derive(Greet2)]
+enum DuplicateVariantContent {
+    #[greet2(name = "farewell", content = "Bye, {name}!")]
+    #[greet2(name = "farewell", content = "Farewell, {name}!")]
+    Guest { name: String },
+}
+
+// Variants with fields need a template for every greeting
+#[derive(Greet2)]
+#[greet2(name = "farewell")]
+enum Visitor {
+    #[greet2(content = "Hi, {name}!")]
+    #[greet2(name = "farewell", content = "Bye, {name}!")]
+    Guest { name: String },
+    #[greet2(content = "Welcome back, {0}!")]
+    Member(String),
+    Anonymous,
+}
+
+#[derive(Greet2)]
+#[greet2(name = "farewell", content = "Bye, {nmae}!")]
+struct UnknownField {
+    name: String,
+}
+
+fn main() {}
@@ -0,0 +1,51 @@
+use derive::Greet2;
+
+// Paths are relative to the manifest of the crate being compiled, which
+// trybuild generates in `target/tests/trybuild/derive`.
+
+#[derive(Greet2)]
+#[greet2(path = "missing.toml", key = "welcome")]
+struct MissingFile {
+    name: String,
+}
+
+#[derive(Greet2)]
+#[greet2(path = "../../../../derive/tests/ui/invalid.toml", key = "welcome")]
+struct InvalidToml {
+    name: String,
+}
+
+#[derive(Greet2)]
+#[greet2(path = "../../../../derive/tests/ui/templates.toml", key = "farewell")]
+#[greet2(locale = "vi", key = "vi.farewell")]
+struct MissingKey {
+    name: String,
+}
+
+#[derive(Greet2)]
+#[greet2(path = "../../../../derive/tests/ui/templates.toml", key = "count")]
+#[greet2(name = "vi", key = "vi")]
+struct NotAString {
+    name: String,
+}
+
+#[derive(Greet2)]
+#[greet2(path = "../../../../derive/tests/ui/templates.toml")]
+#[greet2(key = "welcome", content = "Hi, I am {name}.")]
+struct ContentAndKey {
+    name: String,
+}
+
+#[derive(Greet2)]
+#[greet2(key = "welcome")]
+struct KeyWithoutPath {
+    name: String,
+}
+
+#[derive(Greet2)]
+#[greet2(path = "../../../../derive/tests/ui/templates.toml", key = "typo")]
+struct TemplateError {
+    name: String,
+}
+
+fn main() {}
@@ -0,0 +1,20 @@
+use derive::{greet, Greet2};
+
+#[derive(Greet2)]
+#[greet2(content = "Hi, I am {name")]
+struct Unterminated {
+    name: String,
+}
+
+#[derive(Greet2)]
+#[greet2(content = "Hi, I am name}.")]
+struct Stray {
+    name: String,
+}
+
+#[greet(content = "Braces are written {{name}} and }} but not }.")]
+struct Escaped {
+    name: String,
+}
+
+fn main() {}
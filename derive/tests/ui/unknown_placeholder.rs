@@ -0,0 +1,26 @@
+use derive::{add_greet, greet, Greet, Greet2};
+
+#[derive(Greet2)]
+#[greet2(content = "Hi, I am {nmae}.")]
+struct Misspelled {
+    name: String,
+}
+
+#[derive(Greet)]
+#[greet(content = "I am {age} years old.")]
+struct Unknown {
+    name: String,
+}
+
+#[greet(content = "{titel} {name}")]
+struct Attribute {
+    title: String,
+    name: String,
+}
+
+add_greet!(
+    #[greet(content = "{0} and {1}")]
+    struct Tuple(String);
+);
+
+fn main() {}
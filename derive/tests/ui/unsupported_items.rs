@@ -0,0 +1,23 @@
+use derive::{add_greet, greet, Greet, Greet2};
+
+#[derive(Greet2)]
+#[greet2(content = "Hi")]
+union Number {
+    int: u32,
+    float: f32,
+}
+
+#[greet(content = "Hi")]
+fn not_a_type() {}
+
+add_greet!(
+    fn not_a_struct_either() {}
+);
+
+#[derive(Greet)]
+#[greet(content = "Hi")]
+union Bits {
+    int: u32,
+}
+
+fn main() {}
@@ -0,0 +1,253 @@
+//! The built-in filters of placeholders such as `{name|upper}` or
+//! `{bio|truncate:40}`.
+//!
+//! A filter is a function taking the value, as anything `Display`, followed
+//! by the filter's arguments, and returning what to write instead:
+//!
+//! ```
+//! use core::fmt::Display;
+//!
+//! pub fn shout(value: impl Display) -> impl Display {
+//!     format!("{value}!")
+//! }
+//! ```
+//!
+//! Functions of that shape in a module named by `filters = path::to::module`
+//! can be used in templates like the built-in ones. Filters chain from left
+//! to right: `{name|trim|title_case}` is `title_case(trim(&name))`.
+
+use core::fmt::{self, Display, Write};
+
+/// Writes `value` in uppercase.
+pub fn upper(value: impl Display) -> impl Display {
+    Map(value, |c, w| {
+        c.to_uppercase().try_for_each(|c| w.write_char(c))
+    })
+}
+
+/// Writes `value` in lowercase.
+pub fn lower(value: impl Display) -> impl Display {
+    Map(value, |c, w| {
+        c.to_lowercase().try_for_each(|c| w.write_char(c))
+    })
+}
+
+/// Writes `value` with its first character in uppercase.
+pub fn capitalize(value: impl Display) -> impl Display {
+    Words {
+        value,
+        every_word: false,
+    }
+}
+
+/// Writes `value` with the first letter of every word in uppercase and the
+/// others in lowercase.
+pub fn title_case(value: impl Display) -> impl Display {
+    Words {
+        value,
+        every_word: true,
+    }
+}
+
+/// Writes `value` without leading and trailing whitespace.
+pub fn trim(value: impl Display) -> impl Display {
+    Trim(value)
+}
+
+/// Writes the first `max` characters of `value`, followed by `…` if it is
+/// longer.
+pub fn truncate(value: impl Display, max: usize) -> impl Display {
+    Truncate { value, max }
+}
+
+/// Writes what `write` writes into `f`, padded as `f` asks for.
+fn pad(
+    f: &mut fmt::Formatter<'_>,
+    write: impl FnOnce(&mut dyn Write) -> fmt::Result,
+) -> fmt::Result {
+    if f.width().is_none() && f.precision().is_none() {
+        write(f)
+    } else {
+        let mut s = String::new();
+        write(&mut s)?;
+        f.pad(&s)
+    }
+}
+
+/// Writes a value through a function of each of its characters.
+struct Map<T>(T, fn(char, &mut dyn Write) -> fmt::Result);
+
+impl<T: Display> Display for Map<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        pad(f, |w| write!(CharWriter(w, self.1), "{}", self.0))
+    }
+}
+
+struct Words<T> {
+    value: T,
+    every_word: bool,
+}
+
+impl<T: Display> Display for Words<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        pad(f, |w| {
+            let mut at_start = true;
+            let mut started = false;
+            let mut writer = CharWriter(w, |c: char, w: &mut dyn Write| {
+                let first = at_start && (self.every_word || !started);
+                at_start = c.is_whitespace();
+                started |= !c.is_whitespace();
+                if first {
+                    c.to_uppercase().try_for_each(|c| w.write_char(c))
+                } else if self.every_word {
+                    c.to_lowercase().try_for_each(|c| w.write_char(c))
+                } else {
+                    w.write_char(c)
+                }
+            });
+            write!(writer, "{}", self.value)
+        })
+    }
+}
+
+struct Trim<T>(T);
+
+impl<T: Display> Display for Trim<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Trailing whitespace is only known to be trailing at the end, so it
+        // is held back until something else follows it.
+        pad(f, |w| {
+            let mut started = false;
+            let mut held = String::new();
+            let mut writer = CharWriter(w, |c: char, w: &mut dyn Write| {
+                if c.is_whitespace() {
+                    if started {
+                        held.push(c);
+                    }
+                    return Ok(());
+                }
+                started = true;
+                w.write_str(&held)?;
+                held.clear();
+                w.write_char(c)
+            });
+            write!(writer, "{}", self.0)
+        })
+    }
+}
+
+struct Truncate<T> {
+    value: T,
+    max: usize,
+}
+
+impl<T: Display> Display for Truncate<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        pad(f, |w| {
+            let mut written = 0;
+            let mut writer = CharWriter(w, |c: char, w: &mut dyn Write| {
+                written += 1;
+                match written.cmp(&(self.max + 1)) {
+                    core::cmp::Ordering::Less => w.write_char(c),
+                    core::cmp::Ordering::Equal => w.write_char('…'),
+                    core::cmp::Ordering::Greater => Ok(()),
+                }
+            });
+            write!(writer, "{}", self.value)
+        })
+    }
+}
+
+/// A `fmt::Write` passing every character through a function.
+struct CharWriter<'w, F>(&'w mut dyn Write, F);
+
+impl<F> Write for CharWriter<'_, F>
+where
+    F: FnMut(char, &mut dyn Write) -> fmt::Result,
+{
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        s.chars().try_for_each(|c| (self.1)(c, self.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Writes its strings one `write_str` at a time, as values formatting
+    /// their parts separately do.
+    struct Pieces(&'static [&'static str]);
+
+    impl Display for Pieces {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            self.0.iter().try_for_each(|piece| f.write_str(piece))
+        }
+    }
+
+    #[test]
+    fn case() {
+        assert_eq!(upper("straße 1").to_string(), "STRASSE 1");
+        assert_eq!(lower("ÀB Ĉ").to_string(), "àb ĉ");
+    }
+
+    #[test]
+    fn capitalize_and_title_case() {
+        assert_eq!(capitalize("hELLO wORLD").to_string(), "HELLO wORLD");
+        assert_eq!(title_case("hELLO wORLD").to_string(), "Hello World");
+        // Leading whitespace is kept, and the first word still counts
+        assert_eq!(capitalize("  hello world").to_string(), "  Hello world");
+        assert_eq!(title_case("  hELLO\twORLD ").to_string(), "  Hello\tWorld ");
+        assert_eq!(capitalize("élan").to_string(), "Élan");
+        assert_eq!(capitalize("").to_string(), "");
+        assert_eq!(
+            title_case(Pieces(&["an", "na b", "ob"])).to_string(),
+            "Anna Bob"
+        );
+    }
+
+    #[test]
+    fn trim_whitespace() {
+        assert_eq!(trim("  a  b \n").to_string(), "a  b");
+        assert_eq!(trim(" \t ").to_string(), "");
+        assert_eq!(trim("ab").to_string(), "ab");
+        // Whitespace between pieces is held back until something follows it
+        assert_eq!(trim(Pieces(&[" a ", " ", " b", "  "])).to_string(), "a   b");
+        assert_eq!(trim(Pieces(&["a", " ", ""])).to_string(), "a");
+    }
+
+    #[test]
+    fn truncate_at_max() {
+        assert_eq!(truncate("hello", 6).to_string(), "hello");
+        assert_eq!(truncate("hello", 5).to_string(), "hello");
+        assert_eq!(truncate("hello", 4).to_string(), "hell…");
+        assert_eq!(truncate("hello", 0).to_string(), "…");
+        assert_eq!(truncate("", 0).to_string(), "");
+        // Counted in characters, not bytes
+        assert_eq!(truncate("héllo wörld", 7).to_string(), "héllo w…");
+        assert_eq!(truncate("日本語", 3).to_string(), "日本語");
+        assert_eq!(truncate("日本語", 2).to_string(), "日本…");
+        assert_eq!(truncate(Pieces(&["ab", "cd", "ef"]), 3).to_string(), "abc…");
+    }
+
+    #[test]
+    fn chained() {
+        assert_eq!(
+            title_case(trim("  hIEU nguyen ")).to_string(),
+            "Hieu Nguyen"
+        );
+        assert_eq!(truncate(upper("hello"), 2).to_string(), "HE…");
+    }
+
+    #[test]
+    fn padding() {
+        assert_eq!(format!("[{:>6}]", upper("ab")), "[    AB]");
+        assert_eq!(format!("[{:*^9}]", title_case("ab cd")), "[**Ab Cd**]");
+        assert_eq!(format!("[{:<4}]", trim(" x ")), "[x   ]");
+        // Widths count the characters written, `…` included
+        assert_eq!(format!("[{:>6}]", truncate("hello", 3)), "[  hel…]");
+        assert_eq!(format!("[{:.2}]", lower("ABC")), "[ab]");
+        assert_eq!(format!("[{:>4.2}]", capitalize("abc")), "[  Ab]");
+        // Without a width or precision nothing is buffered or padded
+        assert_eq!(format!("[{}]", capitalize(" a")), "[ A]");
+    }
+}
@@ -0,0 +1,135 @@
+//! Runtime support for the greet macros in the `derive` crate.
+//!
+//! `add_greet!`, `#[derive(Greet)]`, `#[greet(...)]` and `#[derive(Greet2)]`
+//! all implement the [`Greet`] trait defined here, so greeters can be used
+//! generically (`fn welcome<T: Greet>(x: &T)`) or as trait objects
+//! (`Vec<Box<dyn Greet>>`).
+
+use core::fmt;
+use std::io::{self, Write as _};
+
+pub mod filters;
+pub mod locale;
+pub mod plural;
+
+/// A type that can introduce itself.
+///
+/// Only [`Greet::write_greeting`] has to be implemented; the other methods
+/// are built on top of it and write the greeting straight into their
+/// destination without building an intermediate `String`. The trait is
+/// object safe.
+pub trait Greet {
+    /// Writes the greeting into `w`.
+    ///
+    /// Any `&mut W` with `W: fmt::Write`, such as a `String` or a
+    /// `fmt::Formatter`, can be passed.
+    fn write_greeting(&self, w: &mut dyn fmt::Write) -> fmt::Result;
+
+    /// Writes the greeting into the byte sink `w`, such as a file, a socket
+    /// or a `Vec<u8>`.
+    fn write_greeting_io(&self, w: &mut dyn io::Write) -> io::Result<()> {
+        write!(w, "{}", Greeting(self))
+    }
+
+    /// Returns the greeting as a `String`.
+    fn greeting(&self) -> String {
+        Greeting(self).to_string()
+    }
+
+    /// Prints the greeting to stdout, followed by a newline.
+    ///
+    /// # Panics
+    ///
+    /// Panics if writing to stdout fails, like `println!`.
+    fn greet(&self) {
+        let mut stdout = io::stdout().lock();
+        if let Err(e) = writeln!(stdout, "{}", Greeting(self)) {
+            panic!("failed printing to stdout: {e}");
+        }
+    }
+}
+
+/// Displays a greeter's greeting.
+struct Greeting<'a, T: ?Sized>(&'a T);
+
+impl<T: Greet + ?Sized> fmt::Display for Greeting<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.write_greeting(f)
+    }
+}
+
+/// Formats `greeter`'s greeting with `f`, honouring its width, fill,
+/// alignment and precision.
+///
+/// This is how `#[greet(display)]` types implement `Display`.
+pub fn fmt_greeting<T: Greet + ?Sized>(greeter: &T, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    if f.width().is_none() && f.precision().is_none() {
+        greeter.write_greeting(f)
+    } else {
+        f.pad(&greeter.greeting())
+    }
+}
+
+/// Creates a greeter whose greeting is written by `f`.
+///
+/// Named greetings such as `#[greet2(name = "farewell", ...)]` use it to
+/// share the provided [`Greet`] methods.
+pub fn from_fn<F>(f: F) -> FromFn<F>
+where
+    F: Fn(&mut dyn fmt::Write) -> fmt::Result,
+{
+    FromFn(f)
+}
+
+/// A greeter created by [`from_fn`].
+pub struct FromFn<F>(F);
+
+impl<F> Greet for FromFn<F>
+where
+    F: Fn(&mut dyn fmt::Write) -> fmt::Result,
+{
+    fn write_greeting(&self, w: &mut dyn fmt::Write) -> fmt::Result {
+        (self.0)(w)
+    }
+}
+
+impl<T: Greet + ?Sized> Greet for &T {
+    fn write_greeting(&self, w: &mut dyn fmt::Write) -> fmt::Result {
+        (**self).write_greeting(w)
+    }
+}
+
+impl<T: Greet + ?Sized> Greet for Box<T> {
+    fn write_greeting(&self, w: &mut dyn fmt::Write) -> fmt::Result {
+        (**self).write_greeting(w)
+    }
+}
+
+/// Formats a borrowed value with a function instead of the value's own
+/// formatting traits.
+///
+/// This is what `#[greet(with = path::to::fmt_fn)]` fields expand to. The
+/// function takes the field by reference, or anything the reference derefs
+/// to (`&str` for a `String` field), and a `&mut fmt::Formatter`.
+pub struct FormatWith<'a, T: ?Sized> {
+    value: &'a T,
+    fmt: fn(&T, &mut fmt::Formatter<'_>) -> fmt::Result,
+}
+
+impl<'a, T: ?Sized> FormatWith<'a, T> {
+    pub fn new(value: &'a T, fmt: fn(&T, &mut fmt::Formatter<'_>) -> fmt::Result) -> Self {
+        Self { value, fmt }
+    }
+}
+
+impl<T: ?Sized> fmt::Display for FormatWith<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        (self.fmt)(self.value, f)
+    }
+}
+
+impl<T: ?Sized> fmt::Debug for FormatWith<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        (self.fmt)(self.value, f)
+    }
+}
@@ -0,0 +1,89 @@
+//! Locale matching for localized greetings such as
+//! `#[greet2(locale = "vi", content = "...")]`.
+//!
+//! Locales are BCP 47 tags like `vi-VN`. They are compared ignoring case,
+//! with `_` and `-` interchangeable, so `vi_vn` finds the `vi-VN` template.
+
+use core::iter;
+
+/// Whether locales `a` and `b` are the same.
+pub fn matches(a: &str, b: &str) -> bool {
+    let normalize = |c: u8| match c {
+        b'_' => b'-',
+        c => c.to_ascii_lowercase(),
+    };
+    a.len() == b.len() && iter::zip(a.bytes(), b.bytes()).all(|(a, b)| normalize(a) == normalize(b))
+}
+
+/// The locales to try for `locale`, most specific first.
+///
+/// If one of `chains` starts with `locale`, that chain is tried, e.g.
+/// `["es-419", "es-MX", "es"]`. Otherwise `locale` is followed by its
+/// parents: `vi-VN` by `vi`. The default locale comes after all of them and
+/// is not included.
+pub fn fallbacks<'a>(
+    locale: &'a str,
+    chains: &'a [&'a [&'a str]],
+) -> impl Iterator<Item = &'a str> {
+    let chain = chains
+        .iter()
+        .find(|chain| chain.first().is_some_and(|first| matches(first, locale)));
+    let parents = chain.is_none().then(|| {
+        iter::successors(Some(locale), |locale| {
+            locale.rfind(['-', '_']).map(|end| &locale[..end])
+        })
+    });
+    chain
+        .into_iter()
+        .flat_map(|chain| chain.iter().copied())
+        .chain(parents.into_iter().flatten())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matching() {
+        assert!(matches("vi", "vi"));
+        assert!(matches("vi-VN", "vi_vn"));
+        assert!(matches("EN-us", "en-US"));
+        assert!(!matches("vi", "vi-VN"));
+        assert!(!matches("en-US", "en-GB"));
+        assert!(!matches("pt", "pl"));
+        assert!(matches("", ""));
+    }
+
+    #[test]
+    fn parents() {
+        let none: &[&[&str]] = &[];
+        assert_eq!(fallbacks("vi", none).collect::<Vec<_>>(), ["vi"]);
+        assert_eq!(
+            fallbacks("vi-VN", none).collect::<Vec<_>>(),
+            ["vi-VN", "vi"]
+        );
+        assert_eq!(
+            fallbacks("zh_Hant-TW", none).collect::<Vec<_>>(),
+            ["zh_Hant-TW", "zh_Hant", "zh"]
+        );
+    }
+
+    #[test]
+    fn chains() {
+        let chains: &[&[&str]] = &[&["pt-BR", "pt-PT", "es"], &["es-419", "es-MX", "es"]];
+        assert_eq!(
+            fallbacks("pt_br", chains).collect::<Vec<_>>(),
+            ["pt-BR", "pt-PT", "es"]
+        );
+        assert_eq!(
+            fallbacks("ES-419", chains).collect::<Vec<_>>(),
+            ["es-419", "es-MX", "es"]
+        );
+        // Chains only apply to the locale they start with
+        assert_eq!(fallbacks("pt", chains).collect::<Vec<_>>(), ["pt"]);
+        assert_eq!(
+            fallbacks("pt-PT", chains).collect::<Vec<_>>(),
+            ["pt-PT", "pt"]
+        );
+    }
+}
@@ -0,0 +1,216 @@
+//! Plural rules for templates such as `{age} {age|plural:"year","years"}`.
+//!
+//! A rule is a `fn(u64) -> Category` choosing the form of a word for a
+//! count. Templates use [`english`], or the rules of their locale, unless the
+//! greeting sets `plural_rules = path::to::rule`; any function of that type
+//! can be used for languages not covered here.
+
+/// The plural categories of the Unicode CLDR, which every language's forms
+/// fall into.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Category {
+    Zero,
+    One,
+    Two,
+    Few,
+    Many,
+    Other,
+}
+
+/// English, and the many languages with the same rule: `one` for 1,
+/// `other` for everything else.
+pub fn english(n: u64) -> Category {
+    match n {
+        1 => Category::One,
+        _ => Category::Other,
+    }
+}
+
+/// French: `one` for 0 and 1, `many` for multiples of a million, `other`
+/// for everything else.
+pub fn french(n: u64) -> Category {
+    match n {
+        0 | 1 => Category::One,
+        _ if n.is_multiple_of(1_000_000) => Category::Many,
+        _ => Category::Other,
+    }
+}
+
+/// Russian, Ukrainian and the like: `one` for 1, 21, 31, ..., `few` for 2-4,
+/// 22-24, ..., `many` for everything else.
+pub fn russian(n: u64) -> Category {
+    match (n % 10, n % 100) {
+        (1, rem) if rem != 11 => Category::One,
+        (2..=4, rem) if !(12..=14).contains(&rem) => Category::Few,
+        _ => Category::Many,
+    }
+}
+
+/// Polish: `one` for 1, `few` for 2-4, 22-24, ..., `many` for everything
+/// else, including 12-14.
+pub fn polish(n: u64) -> Category {
+    match (n, n % 10, n % 100) {
+        (1, _, _) => Category::One,
+        (_, 2..=4, rem) if !(12..=14).contains(&rem) => Category::Few,
+        _ => Category::Many,
+    }
+}
+
+/// Vietnamese, Japanese, Chinese and the other languages without plural
+/// forms: always `other`.
+pub fn invariant(_: u64) -> Category {
+    Category::Other
+}
+
+/// The rules of the language of `locale`, by its first subtag: `ru` in
+/// `ru-RU`. The region only matters for Portuguese, which counts like
+/// English in Portugal and like French elsewhere. Languages without rules
+/// here get [`english`].
+///
+/// Localized greetings use the rules of their locale unless they set
+/// `plural_rules`.
+pub fn rules_for(locale: &str) -> fn(u64) -> Category {
+    let mut subtags = locale.split(['-', '_']);
+    let language = subtags.next().unwrap_or_default();
+    match language.to_ascii_lowercase().as_str() {
+        "pt" if subtags.any(|subtag| subtag.eq_ignore_ascii_case("pt")) => english,
+        "fr" | "pt" => french,
+        "ru" | "uk" | "be" => russian,
+        "pl" => polish,
+        "vi" | "ja" | "zh" | "ko" | "th" | "id" | "ms" => invariant,
+        _ => english,
+    }
+}
+
+/// A count plural rules can choose a form for: any primitive integer, or a
+/// reference to one. Negative counts use the form of their magnitude.
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` is not a count that plural forms can be chosen for",
+    label = "`plural` needs an integer"
+)]
+pub trait Count {
+    fn count(&self) -> u64;
+}
+
+macro_rules! impl_count {
+    (unsigned: $($ty:ty),*; signed: $($signed:ty),*) => {
+        $(impl Count for $ty {
+            fn count(&self) -> u64 {
+                u64::try_from(*self).unwrap_or(u64::MAX)
+            }
+        })*
+        $(impl Count for $signed {
+            fn count(&self) -> u64 {
+                u64::try_from(self.unsigned_abs()).unwrap_or(u64::MAX)
+            }
+        })*
+    };
+}
+
+impl_count!(unsigned: u8, u16, u32, u64, u128, usize; signed: i8, i16, i32, i64, i128, isize);
+
+impl<T: Count + ?Sized> Count for &T {
+    fn count(&self) -> u64 {
+        (**self).count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use Category::*;
+
+    fn forms(rule: fn(u64) -> Category, counts: &[u64]) -> Vec<Category> {
+        counts.iter().map(|&n| rule(n)).collect()
+    }
+
+    #[test]
+    fn english_rule() {
+        assert_eq!(
+            forms(english, &[0, 1, 2, 11, 21, 101]),
+            [Other, One, Other, Other, Other, Other]
+        );
+    }
+
+    #[test]
+    fn french_rule() {
+        assert_eq!(forms(french, &[0, 1, 2, 999_999]), [One, One, Other, Other]);
+        // `many` for multiples of a million only
+        assert_eq!(
+            forms(french, &[1_000_000, 1_000_001, 2_000_000, 10_000_000]),
+            [Many, Other, Many, Many]
+        );
+    }
+
+    #[test]
+    fn russian_rule() {
+        assert_eq!(forms(russian, &[1, 21, 101, 1001]), [One, One, One, One]);
+        assert_eq!(
+            forms(russian, &[2, 4, 22, 24, 102, 1004]),
+            [Few, Few, Few, Few, Few, Few]
+        );
+        assert_eq!(
+            forms(russian, &[0, 5, 11, 12, 14, 19, 20, 111, 112, 1011]),
+            [Many, Many, Many, Many, Many, Many, Many, Many, Many, Many]
+        );
+    }
+
+    #[test]
+    fn polish_rule() {
+        assert_eq!(polish(1), One);
+        assert_eq!(
+            forms(polish, &[2, 3, 4, 22, 23, 24, 102]),
+            [Few, Few, Few, Few, Few, Few, Few]
+        );
+        // Unlike Russian, 21 is not `one`
+        assert_eq!(
+            forms(polish, &[0, 5, 11, 12, 13, 14, 21, 25, 112, 1000]),
+            [Many, Many, Many, Many, Many, Many, Many, Many, Many, Many]
+        );
+    }
+
+    #[test]
+    fn invariant_rule() {
+        assert_eq!(forms(invariant, &[0, 1, 2]), [Other, Other, Other]);
+    }
+
+    #[test]
+    fn rules_by_locale() {
+        // Counts telling the rules apart
+        let counts = [0, 1, 2, 5, 21, 22, 1_000_000];
+        let rules_of = |locale| forms(rules_for(locale), &counts);
+        for (locale, rule) in [
+            ("en", english as fn(u64) -> Category),
+            ("en-US", english),
+            ("fr", french),
+            ("fr-CA", french),
+            ("FR", french),
+            ("pt", french),
+            ("pt-BR", french),
+            ("pt-PT", english),
+            ("pt_pt", english),
+            ("pt-Latn-PT", english),
+            ("ru", russian),
+            ("uk-UA", russian),
+            ("be", russian),
+            ("pl", polish),
+            ("pl-PL", polish),
+            ("vi", invariant),
+            ("ja", invariant),
+            ("zh-Hant", invariant),
+            ("de", english),
+            ("", english),
+        ] {
+            assert_eq!(rules_of(locale), forms(rule, &counts), "{locale}");
+        }
+    }
+
+    #[test]
+    fn counts() {
+        assert_eq!(3u8.count(), 3);
+        assert_eq!((-3i32).count(), 3);
+        assert_eq!(i64::MIN.count(), 1 << 63);
+        assert_eq!(u128::MAX.count(), u64::MAX);
+        assert_eq!((&&7usize).count(), 7);
+    }
+}
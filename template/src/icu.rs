@@ -0,0 +1,458 @@
+//! Parsing of templates written in ICU MessageFormat, for greetings with
+//! `syntax = "icu"`, e.g. `"{name} is {age, plural, one {# year} other {# years}} old."`.
+//!
+//! Messages are parsed into the same segments as the default syntax, and are
+//! lowered by the same code. Simple arguments `{name}` and `{name, number}`
+//! are supported, as are `plural`, with `offset:` and `=N` selectors, and
+//! `select`. Apostrophes quote as in ICU: `''` is an apostrophe, and `'{'` a
+//! literal brace.
+
+use darling::{error::Accumulator, Error};
+use proc_macro2::Span;
+use syn::LitStr;
+
+use crate::{Argument, Placeholder, Segment, SpanMap, Template, PLURAL_CATEGORIES};
+
+/// Parses `lit`, pushing every malformed argument and stray brace to
+/// `errors`, like [`Template::parse`].
+pub(crate) fn parse(lit: &LitStr, errors: &mut Accumulator) -> Template {
+    let src = lit.value();
+    let mut parser = Parser {
+        src: &src,
+        pos: 0,
+        spans: SpanMap::new(lit, &src),
+        errors,
+        in_plural: false,
+    };
+    let mut segments = parser.message();
+    // A top-level message only stops early at a `}` closing nothing.
+    while parser.pos < src.len() {
+        let start = parser.pos;
+        parser.bump();
+        let span = parser.spans.span(start..parser.pos);
+        parser.errors.push(
+            Error::custom("unmatched `}` in template: use `'}'` for a literal brace")
+                .with_span(&span),
+        );
+        segments.extend(parser.message());
+    }
+    Template { segments }
+}
+
+/// Messages by their selector, e.g. `one` in `one {# year}`.
+type Cases = Vec<(String, Vec<Segment>)>;
+
+struct Parser<'s, 'e> {
+    src: &'s str,
+    /// The byte offset of the next character.
+    pos: usize,
+    spans: SpanMap<'s>,
+    errors: &'e mut Accumulator,
+    /// Whether the message being parsed belongs to a `plural`, where `#` is
+    /// the count.
+    in_plural: bool,
+}
+
+impl Parser<'_, '_> {
+    fn peek(&self) -> Option<char> {
+        self.src[self.pos..].chars().next()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += c.len_utf8();
+        Some(c)
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        let eaten = self.peek() == Some(c);
+        if eaten {
+            self.pos += c.len_utf8();
+        }
+        eaten
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.bump();
+        }
+    }
+
+    /// A name, keyword or selector, up to the next whitespace or syntax
+    /// character.
+    fn word(&mut self) -> &str {
+        let start = self.pos;
+        while self
+            .peek()
+            .is_some_and(|c| !c.is_whitespace() && !matches!(c, '{' | '}' | ',' | '\'' | '#'))
+        {
+            self.bump();
+        }
+        &self.src[start..self.pos]
+    }
+
+    /// Parses a message up to the end of the template or to the `}` closing
+    /// it, which is left in place.
+    fn message(&mut self) -> Vec<Segment> {
+        let mut segments = Vec::new();
+        let mut text = String::new();
+        while let Some(c) = self.peek() {
+            match c {
+                '}' => break,
+                '{' => {
+                    if !text.is_empty() {
+                        segments.push(Segment::Text(std::mem::take(&mut text)));
+                    }
+                    segments.extend(self.argument());
+                }
+                '#' if self.in_plural => {
+                    if !text.is_empty() {
+                        segments.push(Segment::Text(std::mem::take(&mut text)));
+                    }
+                    let start = self.pos;
+                    self.bump();
+                    segments.push(Segment::PluralCount(self.spans.span(start..self.pos)));
+                }
+                '\'' => {
+                    self.bump();
+                    self.quoted(&mut text);
+                }
+                c => {
+                    self.bump();
+                    text.push(c);
+                }
+            }
+        }
+        if !text.is_empty() {
+            segments.push(Segment::Text(text));
+        }
+        segments
+    }
+
+    /// Continues after an apostrophe: `''` is an apostrophe, and an
+    /// apostrophe before a syntax character quotes the text up to the next
+    /// one. Any other apostrophe is literal.
+    fn quoted(&mut self, text: &mut String) {
+        match self.peek() {
+            Some('\'') => {
+                self.bump();
+                text.push('\'');
+            }
+            Some('{' | '}') => self.quote(text),
+            Some('#') if self.in_plural => self.quote(text),
+            _ => text.push('\''),
+        }
+    }
+
+    fn quote(&mut self, text: &mut String) {
+        while let Some(c) = self.bump() {
+            match c {
+                '\'' if self.eat('\'') => text.push('\''),
+                '\'' => return,
+                c => text.push(c),
+            }
+        }
+    }
+
+    /// The byte offset just after the `}` closing the argument starting at
+    /// `start`, or the end of the template if it is unterminated.
+    fn argument_end(&self, start: usize) -> usize {
+        let mut depth = 0usize;
+        let mut chars = self.src[start..].char_indices().peekable();
+        while let Some((i, c)) = chars.next() {
+            match c {
+                '\'' if chars.next_if(|&(_, c)| c == '\'').is_some() => {}
+                '\'' if chars
+                    .peek()
+                    .is_some_and(|&(_, c)| matches!(c, '{' | '}' | '#')) =>
+                {
+                    while chars.next_if(|&(_, c)| c != '\'').is_some() {}
+                    chars.next();
+                }
+                '{' => depth += 1,
+                '}' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return start + i + 1;
+                    }
+                }
+                _ => {}
+            }
+        }
+        self.src.len()
+    }
+
+    /// Parses an argument from its `{` to its `}`. A malformed argument is
+    /// reported and skipped.
+    fn argument(&mut self) -> Option<Segment> {
+        let start = self.pos;
+        let end = self.argument_end(start);
+        let span = self.spans.span(start..end);
+        match self.argument_body(span) {
+            Ok(segment) => Some(segment),
+            Err(error) => {
+                self.errors.push(error.with_span(&span));
+                self.pos = end;
+                None
+            }
+        }
+    }
+
+    fn argument_body(&mut self, span: Span) -> darling::Result<Segment> {
+        self.bump();
+        self.skip_whitespace();
+        let name = self.word();
+        if name.is_empty() {
+            return Err(Error::custom(
+                "argument has no name: name a field, e.g. `{name}`",
+            ));
+        }
+        let arg = Argument::parse_field(name, span).ok_or_else(|| {
+            Error::custom(format!(
+                "invalid argument name `{name}`: expected a field name or position"
+            ))
+        })?;
+        self.skip_whitespace();
+        if self.eat('}') {
+            return Ok(placeholder(arg, span));
+        }
+        if !self.eat(',') {
+            return Err(Error::custom("expected `,` or `}` after the argument name"));
+        }
+        self.skip_whitespace();
+        let kind = self.word().to_string();
+        self.skip_whitespace();
+        match kind.as_str() {
+            "number" if self.eat('}') => Ok(placeholder(arg, span)),
+            "number" => Err(Error::custom(
+                "number styles are not supported: use `{name, number}`",
+            )),
+            "plural" | "select" if !self.eat(',') => Err(Error::custom(format!(
+                "expected `,` and the messages after `{kind}`"
+            ))),
+            "plural" => self.plural(arg, span),
+            "select" => self.select(arg, span),
+            "" => Err(Error::custom(
+                "expected an argument type: `number`, `plural` or `select`",
+            )),
+            kind => Err(Error::custom(format!(
+                "unsupported argument type `{kind}`: expected `number`, `plural` or `select`"
+            ))),
+        }
+    }
+
+    /// Parses the selectors and messages of a `plural` or `select` up to its
+    /// closing `}`, returning them with the `other` message.
+    fn cases(&mut self, in_plural: bool) -> darling::Result<(Cases, Vec<Segment>)> {
+        let mut cases = Cases::new();
+        loop {
+            self.skip_whitespace();
+            if self.eat('}') {
+                break;
+            }
+            if self.peek().is_none() {
+                return Err(Error::custom("unterminated argument: expected `}`"));
+            }
+            let selector = self.word().to_string();
+            if selector.is_empty() {
+                return Err(Error::custom("expected a selector followed by `{message}`"));
+            }
+            self.skip_whitespace();
+            if !self.eat('{') {
+                return Err(Error::custom(format!(
+                    "expected `{{message}}` after selector `{selector}`"
+                )));
+            }
+            let outer = self.in_plural;
+            self.in_plural |= in_plural;
+            let message = self.message();
+            self.in_plural = outer;
+            if !self.eat('}') {
+                return Err(Error::custom("unterminated message: expected `}`"));
+            }
+            if cases.iter().any(|(other, _)| *other == selector) {
+                return Err(Error::custom(format!("duplicate selector `{selector}`")));
+            }
+            cases.push((selector, message));
+        }
+        match cases.iter().position(|(selector, _)| selector == "other") {
+            Some(i) => {
+                let (_, other) = cases.remove(i);
+                Ok((cases, other))
+            }
+            None => Err(Error::custom("missing the `other` message")),
+        }
+    }
+
+    /// `{count, plural, offset:1 =0 {...} one {...} other {...}}`, after the
+    /// `plural,`.
+    fn plural(&mut self, count: Argument, span: Span) -> darling::Result<Segment> {
+        self.skip_whitespace();
+        let mut offset = 0;
+        if self.src[self.pos..].starts_with("offset:") {
+            self.pos += "offset:".len();
+            self.skip_whitespace();
+            let word = self.word();
+            offset = word.parse().map_err(|_| {
+                Error::custom(format!("invalid offset `{word}`: expected a number"))
+            })?;
+        }
+        let (cases, other) = self.cases(true)?;
+        let mut exact = Vec::new();
+        let mut forms = Vec::new();
+        for (selector, message) in cases {
+            if let Some(n) = selector.strip_prefix('=') {
+                let n = n.parse().map_err(|_| {
+                    Error::custom(format!(
+                        "invalid selector `{selector}`: expected `=` and a number"
+                    ))
+                })?;
+                exact.push((n, message));
+            } else if PLURAL_CATEGORIES.iter().any(|(name, _)| *name == selector) {
+                forms.push((selector, message));
+            } else {
+                let expected = PLURAL_CATEGORIES.map(|(name, _)| format!("`{name}`"));
+                return Err(Error::custom(format!(
+                    "unknown plural selector `{selector}`: expected `=N` or one of {}",
+                    expected.join(", ")
+                )));
+            }
+        }
+        Ok(Segment::Plural {
+            count,
+            offset,
+            exact,
+            forms,
+            other,
+            span,
+        })
+    }
+
+    /// `{gender, select, female {...} other {...}}`, after the `select,`.
+    fn select(&mut self, value: Argument, span: Span) -> darling::Result<Segment> {
+        let (cases, other) = self.cases(false)?;
+        Ok(Segment::Select {
+            value,
+            cases,
+            other,
+            span,
+        })
+    }
+}
+
+/// A placeholder formatting `arg` as it is.
+fn placeholder(arg: Argument, span: Span) -> Segment {
+    Segment::Placeholder(Placeholder {
+        arg,
+        spec: None,
+        filters: Vec::new(),
+        fallback: None,
+        span,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::parse;
+    use crate::Syntax;
+
+    fn ok(src: &str) -> String {
+        parse(src, Syntax::Icu).unwrap_or_else(|errors| panic!("{src}: {errors:?}"))
+    }
+
+    fn err(src: &str) -> Vec<String> {
+        parse(src, Syntax::Icu).expect_err(src)
+    }
+
+    #[test]
+    fn arguments() {
+        assert_eq!(ok("Hi, I am {name}."), "Hi, I am [name].");
+        assert_eq!(ok("{ name }, {0}"), "[name], [0]");
+        assert_eq!(ok("{age, number} years"), "[age] years");
+        assert_eq!(ok("{address.city}"), "[address.city]");
+    }
+
+    #[test]
+    fn quoting() {
+        assert_eq!(ok("It''s {name}'s"), "It's [name]'s");
+        assert_eq!(ok("'{'name'}' is {name}"), "{name} is [name]");
+        assert_eq!(ok("'{name}'"), "{name}");
+        assert_eq!(ok("'{it''s}'"), "{it's}");
+        // `#` is only special within plurals
+        assert_eq!(ok("#{n} '#'"), "#[n] '#'");
+        assert_eq!(
+            ok("{n, plural, other {'#'# and '#' #}}"),
+            "[plural n other(## and # #)]"
+        );
+    }
+
+    #[test]
+    fn plurals() {
+        assert_eq!(
+            ok("{n, plural, one {# cat} other {# cats}}"),
+            "[plural n one(# cat) other(# cats)]"
+        );
+        assert_eq!(
+            ok("{n,plural,=0{none}other{#}}"),
+            "[plural n =0(none) other(#)]"
+        );
+        assert_eq!(
+            ok("{guests, plural, offset:1 =0 {nobody} =1 {{host}} one {{host} and # other} other {{host} and # others}}"),
+            "[plural guests offset=1 =0(nobody) =1([host]) one([host] and # other) other([host] and # others)]"
+        );
+        assert_eq!(
+            ok("{n, plural, zero {z} one {o} two {t} few {f} many {m} other {x}}"),
+            "[plural n zero(z) one(o) two(t) few(f) many(m) other(x)]"
+        );
+    }
+
+    #[test]
+    fn selects() {
+        assert_eq!(
+            ok("{gender, select, female {She} male {He} other {They}} left"),
+            "[select gender female(She) male(He) other(They)] left"
+        );
+        // `#` in a select within a plural is still the count
+        assert_eq!(
+            ok("{n, plural, one {{g, select, female {her #} other {their #}}} other {#}}"),
+            "[plural n one([select g female(her #) other(their #)]) other(#)]"
+        );
+        assert_eq!(
+            ok("{g, select, other {{n, plural, one {# cat} other {# cats}}}}"),
+            "[select g other([plural n one(# cat) other(# cats)])]"
+        );
+    }
+
+    #[test]
+    fn errors() {
+        for (src, error) in [
+            ("{}", "argument has no name: name a field, e.g. `{name}`"),
+            ("{a b}", "expected `,` or `}` after the argument name"),
+            ("{a-b}", "invalid argument name `a-b`: expected a field name or position"),
+            ("{n, date}", "unsupported argument type `date`: expected `number`, `plural` or `select`"),
+            ("{n, }", "expected an argument type: `number`, `plural` or `select`"),
+            ("{n, number, percent}", "number styles are not supported: use `{name, number}`"),
+            ("{n, plural}", "expected `,` and the messages after `plural`"),
+            ("{n, plural, one {#}}", "missing the `other` message"),
+            ("{n, plural, one {a} one {b} other {c}}", "duplicate selector `one`"),
+            ("{n, plural, some {a} other {b}}", "unknown plural selector `some`: expected `=N` or one of `zero`, `one`, `two`, `few`, `many`, `other`"),
+            ("{n, plural, =x {a} other {b}}", "invalid selector `=x`: expected `=` and a number"),
+            ("{n, plural, offset:x other {b}}", "invalid offset `x`: expected a number"),
+            ("{n, plural, one other {b}}", "expected `{message}` after selector `one`"),
+            ("{n, plural, other {b}", "unterminated argument: expected `}`"),
+            ("{n, select, other {b", "unterminated message: expected `}`"),
+            ("{n, select, {b} other {c}}", "expected a selector followed by `{message}`"),
+            ("a } b", "unmatched `}` in template: use `'}'` for a literal brace"),
+        ] {
+            assert_eq!(err(src), [error], "{src}");
+        }
+    }
+
+    #[test]
+    fn recovers_after_errors() {
+        // A malformed argument is skipped up to its closing brace, so the
+        // rest of the template is still checked.
+        assert_eq!(err("{a b} {n, plural, one {#}} {c d}").len(), 3);
+        assert_eq!(err("{n, date} and } and }").len(), 3);
+    }
+}
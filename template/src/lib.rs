@@ -0,0 +1,1471 @@
+//! Parsing of greeting templates such as `"Hello, my name is {name}."`.
+//!
+//! The syntax follows `std::fmt`: `{field}` or `{field:spec}` is replaced by
+//! the value of a field of the annotated type, `{0}` by a tuple field, and
+//! `{{` / `}}` are literal braces. Fields of fields are reached with a path
+//! such as `{address.city}`, optionally spelled `{self.address.city}`, and
+//! any other expression such as `{age + 1}` or `{full_name()}` is evaluated
+//! against `self`. `{nickname|"friend"}` writes a fallback when an `Option`
+//! is `None`, and `{?nickname}...{/nickname}` is a section written only when
+//! it is `Some`. Templates are parsed by the macros themselves so that
+//! mistakes are reported against the template rather than against the
+//! generated code.
+//!
+//! The parser lives in its own crate so that the macros of `derive` and the
+//! `greet-extract` catalog tool read templates the same way.
+
+use std::ops::Range;
+
+use darling::{error::Accumulator, Error};
+use proc_macro2::{Group, Span, TokenStream, TokenTree};
+use syn::{
+    ext::IdentExt,
+    parenthesized,
+    parse::{ParseStream, Parser},
+    punctuated::Punctuated,
+    token,
+    visit::{self, Visit},
+    BinOp, Expr, Ident, Index, Lit, LitStr, Member, Token,
+};
+
+mod icu;
+
+/// Template used by `add_greet!` and `#[derive(Greet)]` when a struct with
+/// named fields has no `#[greet(content = "...")]` attribute.
+pub const DEFAULT_CONTENT: &str = "Hello, my name is {name} and I am {age} years old.";
+
+/// The plural categories forms can be given for, by their name in templates
+/// and their `greet::plural::Category` variant.
+pub const PLURAL_CATEGORIES: [(&str, &str); 6] = [
+    ("zero", "Zero"),
+    ("one", "One"),
+    ("two", "Two"),
+    ("few", "Few"),
+    ("many", "Many"),
+    ("other", "Other"),
+];
+
+#[derive(Debug)]
+pub enum Segment {
+    /// Literal text, with `{{` and `}}` already unescaped.
+    Text(String),
+    Placeholder(Placeholder),
+    /// `{?nickname}...{/nickname}`, written only when the `Option` field is
+    /// `Some`. Within `body`, the field's name refers to the value inside.
+    IfSome {
+        member: Member,
+        body: Vec<Segment>,
+        span: Span,
+    },
+    /// `{if cond}...{else}...{/if}`, with `otherwise` empty when there is no
+    /// `{else}`.
+    If {
+        cond: Box<Expr>,
+        then: Vec<Segment>,
+        otherwise: Vec<Segment>,
+        span: Span,
+    },
+    /// `{#each tags}...{/each}`, with `body` written for every item.
+    Each {
+        each: Each,
+        body: Vec<Segment>,
+        span: Span,
+    },
+    /// ICU's `{count, plural, =0 {...} one {...} other {...}}`: the message
+    /// of the first exact match for `count`, or else the one for the plural
+    /// category of `count` minus `offset`, or else `other`.
+    Plural {
+        count: Argument,
+        offset: u64,
+        exact: Vec<(u64, Vec<Segment>)>,
+        /// The messages by plural category name, e.g. `one`.
+        forms: Vec<(String, Vec<Segment>)>,
+        other: Vec<Segment>,
+        span: Span,
+    },
+    /// ICU's `#` within a plural message: the count minus the offset.
+    PluralCount(Span),
+    /// ICU's `{gender, select, female {...} other {...}}`: the message of the
+    /// case that matches the value as a string, or else `other`.
+    Select {
+        value: Argument,
+        cases: Vec<(String, Vec<Segment>)>,
+        other: Vec<Segment>,
+        span: Span,
+    },
+}
+
+/// The syntax templates are written in.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Syntax {
+    /// The `std::fmt`-like syntax of this module.
+    #[default]
+    Default,
+    /// ICU MessageFormat, parsed by the `icu` module.
+    Icu,
+}
+
+impl Syntax {
+    /// The syntax option `lit` names.
+    pub fn from_lit(lit: &LitStr) -> darling::Result<Self> {
+        match lit.value().as_str() {
+            "default" => Ok(Self::Default),
+            "icu" => Ok(Self::Icu),
+            other => Err(Error::custom(format!(
+                "unknown syntax `{other}`: expected `default` or `icu`"
+            ))
+            .with_span(lit)),
+        }
+    }
+}
+
+/// What an `{#each ...}` tag says, e.g. `tag in tags sep=", "`.
+#[derive(Debug)]
+pub struct Each {
+    /// The name the body knows the item by, `it` unless one is given with
+    /// `{#each tag in tags}`.
+    pub item: String,
+    /// A field, iterated by reference, or an expression, iterated by value.
+    pub collection: Argument,
+    /// Written between items.
+    pub sep: Option<String>,
+    /// Written between the last two items instead of `sep`.
+    pub last: Option<String>,
+}
+
+#[derive(Debug)]
+pub struct Placeholder {
+    /// What the placeholder formats.
+    pub arg: Argument,
+    /// The format spec after the `:`, if any, e.g. `>3` in `{age:>3}`.
+    pub spec: Option<Spec>,
+    /// The filters the value goes through, e.g. `plural:"year","years"` in
+    /// `{age|plural:"year","years"}`.
+    pub filters: Vec<Filter>,
+    /// Written instead of an `Option` value that is `None`, e.g. `friend` in
+    /// `{nickname|"friend"}`.
+    pub fallback: Option<String>,
+    /// The span of the whole placeholder, braces included.
+    pub span: Span,
+}
+
+/// A filter a placeholder's value goes through, such as
+/// `plural:"year","years"` or `plural(one = "year", other = "years")`.
+#[derive(Debug)]
+pub struct Filter {
+    pub name: Ident,
+    /// The arguments after a `:`.
+    pub args: Vec<Lit>,
+    /// The arguments between parentheses.
+    pub named: Vec<(Ident, Lit)>,
+}
+
+#[derive(Debug)]
+pub enum Argument {
+    Field {
+        /// The field the placeholder refers to.
+        member: Member,
+        /// The fields of `member` followed to reach the value, e.g. `city` in
+        /// `{address.city}`. They belong to other types and are not checked.
+        path: Vec<Member>,
+    },
+    /// Any other expression. Plain names refer to fields, if there is a
+    /// field of that name and the expression does not bind it, and calls of
+    /// lowercase names to methods: `{age + 1}` is `self.age + 1` and
+    /// `{full_name()}` is `self.full_name()`.
+    Expr(Box<Expr>),
+}
+
+#[derive(Debug)]
+pub struct Template {
+    pub segments: Vec<Segment>,
+}
+
+impl Template {
+    /// Parses `lit`, written in `syntax`, pushing every malformed placeholder, stray brace and
+    /// unbalanced section to `errors`. The returned template keeps the
+    /// well-formed placeholders so that they can still be checked against the
+    /// fields.
+    pub fn parse(lit: &LitStr, syntax: Syntax, errors: &mut Accumulator) -> Self {
+        if syntax == Syntax::Icu {
+            return icu::parse(lit, errors);
+        }
+        let src = lit.value();
+        let spans = SpanMap::new(lit, &src);
+        let mut segments = Vec::new();
+        let mut open = Vec::<Open>::new();
+        let mut text = String::new();
+        let mut chars = src.char_indices().peekable();
+
+        while let Some((start, c)) = chars.next() {
+            match c {
+                '{' if chars.peek().map(|&(_, c)| c) == Some('{') => {
+                    chars.next();
+                    text.push('{');
+                }
+                '}' if chars.peek().map(|&(_, c)| c) == Some('}') => {
+                    chars.next();
+                    text.push('}');
+                }
+                '{' => {
+                    let Some(end) = closing_brace(&src[start..]).map(|i| start + i + 1) else {
+                        errors.push(
+                            Error::custom("unterminated placeholder: expected `}`")
+                                .with_span(&spans.span(start..src.len())),
+                        );
+                        break;
+                    };
+                    while chars.next_if(|&(i, _)| i < end).is_some() {}
+                    let span = spans.span(start..end);
+                    let inner = &src[start + 1..end - 1];
+                    let subspan = |range: Range<usize>| {
+                        spans.span(start + 1 + range.start..start + 1 + range.end)
+                    };
+                    let tag = match Tag::parse(inner, span, &subspan) {
+                        Ok(tag) => tag,
+                        Err(error) => {
+                            errors.push(error);
+                            // A malformed opening tag still opens its section,
+                            // so that the closing tag is not reported as well.
+                            match Section::malformed(inner) {
+                                Some(section) => Tag::Open(section),
+                                None => continue,
+                            }
+                        }
+                    };
+                    if !text.is_empty() {
+                        segments.push(Segment::Text(std::mem::take(&mut text)));
+                    }
+                    match tag {
+                        Tag::Placeholder(placeholder) => {
+                            segments.push(Segment::Placeholder(placeholder));
+                        }
+                        Tag::Open(section) => open.push(Open {
+                            section,
+                            span,
+                            outer: std::mem::take(&mut segments),
+                        }),
+                        Tag::Else => match open.last_mut() {
+                            Some(Open {
+                                section:
+                                    Section::If {
+                                        then: then @ None, ..
+                                    },
+                                ..
+                            }) => *then = Some(std::mem::take(&mut segments)),
+                            Some(Open {
+                                section: Section::Malformed(name),
+                                ..
+                            }) if name == "if" => {}
+                            Some(Open {
+                                section: Section::If { .. },
+                                ..
+                            }) => errors.push(
+                                Error::custom("duplicate `{else}`: an `{if}` has at most one")
+                                    .with_span(&span),
+                            ),
+                            _ => errors.push(
+                                Error::custom(
+                                    "unexpected `{else}`: it must be inside an `{if ...}` section",
+                                )
+                                .with_span(&span),
+                            ),
+                        },
+                        Tag::Close(name) => match open.pop() {
+                            Some(section) if section.section.closing_tag() == name => {
+                                segments = section.close(segments);
+                            }
+                            Some(section) => {
+                                errors.push(
+                                    Error::custom(format!(
+                                        "unexpected `{{/{name}}}`: expected `{{/{}}}`",
+                                        section.section.closing_tag()
+                                    ))
+                                    .with_span(&span),
+                                );
+                                open.push(section);
+                            }
+                            None => errors.push(
+                                Error::custom(format!(
+                                    "unexpected `{{/{name}}}`: there is no open section to close"
+                                ))
+                                .with_span(&span),
+                            ),
+                        },
+                    }
+                }
+                '}' => errors.push(
+                    Error::custom("unmatched `}` in template: use `}}` for a literal brace")
+                        .with_span(&spans.span(start..start + 1)),
+                ),
+                c => text.push(c),
+            }
+        }
+        if !text.is_empty() {
+            segments.push(Segment::Text(text));
+        }
+        // Close what is still open, so that the sections' contents are
+        // checked too.
+        while let Some(section) = open.pop() {
+            errors.push(
+                Error::custom(format!(
+                    "unclosed section: expected `{{/{}}}`",
+                    section.section.closing_tag()
+                ))
+                .with_span(&section.span),
+            );
+            segments = section.close(segments);
+        }
+
+        Self { segments }
+    }
+
+    /// A template consisting of fixed text only.
+    pub fn text(text: String) -> Self {
+        Self {
+            segments: vec![Segment::Text(text)],
+        }
+    }
+}
+
+/// What a pair of braces in a template contains.
+enum Tag {
+    Placeholder(Placeholder),
+    /// The start of a section, such as `{?nickname}`.
+    Open(Section),
+    /// The `{else}` of an `{if ...}` section.
+    Else,
+    /// The end of a section, such as `{/nickname}`.
+    Close(String),
+}
+
+/// A kind of section, with what its opening tag says.
+enum Section {
+    IfSome(Member),
+    If {
+        cond: Box<Expr>,
+        /// The segments before the `{else}`, once it has been seen.
+        then: Option<Vec<Segment>>,
+    },
+    Each(Each),
+    /// A section whose opening tag could not be parsed, by its closing tag.
+    Malformed(String),
+}
+
+/// A section whose body is being parsed.
+struct Open {
+    section: Section,
+    span: Span,
+    /// The segments before the section.
+    outer: Vec<Segment>,
+}
+
+impl Tag {
+    /// Parses the text between the braces, `subspan` mapping its byte ranges
+    /// to spans.
+    fn parse(
+        inner: &str,
+        span: Span,
+        subspan: &dyn Fn(Range<usize>) -> Span,
+    ) -> darling::Result<Self> {
+        if let Some(name) = inner.strip_prefix('?') {
+            return match Argument::parse_field(name, span) {
+                Some(Argument::Field { member, path }) if path.is_empty() => {
+                    Ok(Self::Open(Section::IfSome(member)))
+                }
+                _ => Err(Error::custom(format!(
+                    "invalid section `{{{inner}}}`: expected a field name, e.g. `{{?nickname}}`"
+                ))
+                .with_span(&span)),
+            };
+        }
+        if let Some(cond) = inner
+            .strip_prefix("if")
+            .filter(|cond| cond.starts_with(char::is_whitespace))
+        {
+            return match Argument::parse_expr(cond, span) {
+                Ok(cond) => Ok(Self::Open(Section::If { cond, then: None })),
+                Err(e) => Err(
+                    Error::custom(format!("invalid condition in `{{{inner}}}`: {e}"))
+                        .with_span(&span),
+                ),
+            };
+        }
+        if let Some(each) = inner
+            .strip_prefix("#each")
+            .filter(|each| each.is_empty() || each.starts_with(char::is_whitespace))
+        {
+            return Each::parse(each, span)
+                .map(|each| Self::Open(Section::Each(each)))
+                .map_err(|e| {
+                    Error::custom(format!("invalid section `{{{inner}}}`: {e}")).with_span(&span)
+                });
+        }
+        if inner.trim() == "else" {
+            return Ok(Self::Else);
+        }
+        if let Some(name) = inner.strip_prefix('/') {
+            return Ok(Self::Close(name.trim().to_string()));
+        }
+        Placeholder::parse(inner, span, subspan).map(Self::Placeholder)
+    }
+}
+
+impl Section {
+    /// The section `inner` was meant to open, if it looks like an opening tag.
+    fn malformed(inner: &str) -> Option<Self> {
+        let name = if let Some(name) = inner.strip_prefix('?') {
+            name.trim()
+        } else if inner.starts_with("if ") {
+            "if"
+        } else if inner.starts_with("#each") {
+            "each"
+        } else {
+            return None;
+        };
+        Some(Self::Malformed(name.to_string()))
+    }
+
+    /// The name in the tag closing the section.
+    fn closing_tag(&self) -> String {
+        match self {
+            Self::IfSome(member) => member_name(member),
+            Self::If { .. } => "if".to_string(),
+            Self::Each(_) => "each".to_string(),
+            Self::Malformed(name) => name.clone(),
+        }
+    }
+}
+
+impl Open {
+    /// Closes the section with `body`, returning the segments it belongs to.
+    fn close(self, body: Vec<Segment>) -> Vec<Segment> {
+        let mut segments = self.outer;
+        let segment = match self.section {
+            Section::IfSome(member) => Segment::IfSome {
+                member,
+                body,
+                span: self.span,
+            },
+            Section::If {
+                cond,
+                then: Some(then),
+            } => Segment::If {
+                cond,
+                then,
+                otherwise: body,
+                span: self.span,
+            },
+            Section::If { cond, then: None } => Segment::If {
+                cond,
+                then: body,
+                otherwise: Vec::new(),
+                span: self.span,
+            },
+            Section::Each(each) => Segment::Each {
+                each,
+                body,
+                span: self.span,
+            },
+            Section::Malformed(_) => return segments,
+        };
+        segments.push(segment);
+        segments
+    }
+}
+
+impl Each {
+    /// Parses what follows `#each`.
+    fn parse(src: &str, span: Span) -> syn::Result<Self> {
+        let tokens = respan(src.parse::<TokenStream>()?, span);
+        let parser = |input: ParseStream| {
+            let item = if input.peek(Ident) && input.peek2(Token![in]) {
+                let item = input.parse::<Ident>()?;
+                input.parse::<Token![in]>()?;
+                item.unraw().to_string()
+            } else {
+                "it".to_string()
+            };
+            let collection = input.parse::<Expr>()?;
+            check_side_effects(&collection, span)?;
+            let collection = match field_path(&collection) {
+                Some((member, path)) => Argument::Field { member, path },
+                None => Argument::Expr(Box::new(collection)),
+            };
+
+            let (mut sep, mut last, mut list) = (None, None, false);
+            while !input.is_empty() {
+                let option = input.call(Ident::parse_any)?;
+                let slot = match option.to_string().as_str() {
+                    "list" => {
+                        list = true;
+                        continue;
+                    }
+                    "sep" => &mut sep,
+                    "last" => &mut last,
+                    _ => {
+                        return Err(syn::Error::new(
+                            span,
+                            format!("unknown option `{option}`: expected `sep = \"...\"`, `last = \"...\"` or `list`"),
+                        ))
+                    }
+                };
+                input.parse::<Token![=]>()?;
+                let value = input.parse::<LitStr>()?.value();
+                if slot.replace(value).is_some() {
+                    return Err(syn::Error::new(
+                        span,
+                        format!("duplicate option `{option}`"),
+                    ));
+                }
+            }
+            if list {
+                sep.get_or_insert_with(|| ", ".to_string());
+                last.get_or_insert_with(|| " and ".to_string());
+            }
+            Ok(Self {
+                item,
+                collection,
+                sep,
+                last,
+            })
+        };
+        parser.parse2(tokens)
+    }
+}
+
+impl Filter {
+    fn parse(src: &str, span: Span) -> syn::Result<Self> {
+        let tokens = respan(src.parse::<TokenStream>()?, span);
+        let parser = |input: ParseStream| {
+            let name = input.call(Ident::parse_any)?;
+            let mut args = Vec::new();
+            let mut named = Vec::new();
+            if input.peek(Token![:]) {
+                input.parse::<Token![:]>()?;
+                args = Punctuated::<Lit, Token![,]>::parse_separated_nonempty(input)?
+                    .into_iter()
+                    .collect();
+            } else if input.peek(token::Paren) {
+                let content;
+                parenthesized!(content in input);
+                let pairs = content.parse_terminated(
+                    |input| {
+                        let key = input.call(Ident::parse_any)?;
+                        input.parse::<Token![=]>()?;
+                        Ok((key, input.parse::<Lit>()?))
+                    },
+                    Token![,],
+                )?;
+                named = pairs.into_iter().collect();
+            }
+            Ok(Self { name, args, named })
+        };
+        parser.parse2(tokens)
+    }
+}
+
+impl Placeholder {
+    fn parse(
+        inner: &str,
+        span: Span,
+        subspan: &dyn Fn(Range<usize>) -> Span,
+    ) -> darling::Result<Self> {
+        let (head, filters, fallback) = match find_top_level(inner, '|') {
+            Some(i) => {
+                let (filters, fallback) = Self::parse_pipes(inner, i + 1, span, subspan)?;
+                (&inner[..i], filters, fallback)
+            }
+            None => (inner, Vec::new(), None),
+        };
+        let (arg, spec) = match find_top_level(head, ':') {
+            Some(i) => {
+                let spec = Spec::parse(&head[i + 1..], span).map_err(|error| {
+                    Error::custom(format!("invalid format spec in `{{{inner}}}`: {error}"))
+                        .with_span(&span)
+                })?;
+                (head[..i].trim(), Some(spec))
+            }
+            None => (head.trim(), None),
+        };
+        if arg.is_empty() {
+            return Err(Error::custom(format!(
+                "placeholder `{{{inner}}}` has no argument to format: name a field, e.g. `{{name}}`"
+            ))
+            .with_span(&span));
+        }
+        let arg = match Argument::parse_field(arg, span) {
+            Some(field) => field,
+            None => Argument::parse_expr(arg, span)
+                .map(Argument::Expr)
+                .map_err(|error| {
+                    Error::custom(format!("invalid placeholder `{{{inner}}}`: {error}"))
+                        .with_span(&span)
+                })?,
+        };
+
+        Ok(Self {
+            arg,
+            spec,
+            filters,
+            fallback,
+            span,
+        })
+    }
+
+    /// Parses the filters of placeholder `{inner}`, starting at byte
+    /// `start`, optionally followed by a fallback.
+    fn parse_pipes(
+        inner: &str,
+        mut start: usize,
+        span: Span,
+        subspan: &dyn Fn(Range<usize>) -> Span,
+    ) -> darling::Result<(Vec<Filter>, Option<String>)> {
+        let mut filters = Vec::new();
+        loop {
+            let end = find_top_level(&inner[start..], '|').map(|i| start + i);
+            let raw = &inner[start..end.unwrap_or(inner.len())];
+            let pipe = raw.trim();
+            if pipe.starts_with('"') {
+                let fallback = syn::parse_str::<LitStr>(pipe).map_err(|_| {
+                    Error::custom(format!(
+                        "invalid fallback `{pipe}`: expected a string literal, e.g. `{{nickname|\"friend\"}}`"
+                    ))
+                    .with_span(&span)
+                })?;
+                if end.is_some() {
+                    return Err(Error::custom(format!(
+                        "the fallback `{pipe}` in `{{{inner}}}` must come last"
+                    ))
+                    .with_span(&span));
+                }
+                return Ok((filters, Some(fallback.value())));
+            }
+            let mut filter = Filter::parse(pipe, span).map_err(|error| {
+                Error::custom(format!("invalid filter `{pipe}` in `{{{inner}}}`: {error}"))
+                    .with_span(&span)
+            })?;
+            // Spanned so that unknown filters are reported on their name.
+            let name_start = start + raw.len() - raw.trim_start().len();
+            let name_len = filter.name.to_string().len();
+            filter
+                .name
+                .set_span(subspan(name_start..name_start + name_len));
+            filters.push(filter);
+            match end {
+                Some(end) => start = end + 1,
+                None => return Ok((filters, None)),
+            }
+        }
+    }
+
+    /// The `core::fmt` trait the placeholder formats with, e.g. `Debug` for
+    /// `{x:?}`.
+    pub fn format_trait(&self) -> &'static str {
+        self.spec.as_ref().map_or("Display", Spec::format_trait)
+    }
+}
+
+/// A `std::fmt` format spec such as `>8.2` in `{score:>8.2}`:
+///
+/// ```text
+/// [[fill]align][sign]['#']['0'][width]['.' precision][type]
+/// ```
+#[derive(Debug)]
+pub struct Spec {
+    fill: Option<char>,
+    align: Option<char>,
+    sign: Option<char>,
+    alternate: bool,
+    zero: bool,
+    width: Option<Count>,
+    precision: Option<Count>,
+    ty: &'static str,
+}
+
+/// A width or precision.
+#[derive(Debug)]
+pub enum Count {
+    Literal(usize),
+    /// Read from a `usize` field, as in `{name:>width$}` or `{0:.1$}`.
+    Field(Member),
+}
+
+impl Count {
+    /// Parses a count at the start of `rest`, advancing past it.
+    fn parse(rest: &mut &str, span: Span) -> Option<Self> {
+        // A number, or a field name followed by `$`.
+        let is_word: fn(char) -> bool = if rest.starts_with(|c: char| c.is_ascii_digit()) {
+            |c| c.is_ascii_digit()
+        } else {
+            |c| c.is_alphanumeric() || c == '_'
+        };
+        let len = rest.find(|c: char| !is_word(c)).unwrap_or(rest.len());
+        let (word, after) = rest.split_at(len);
+        let field = after.starts_with('$');
+        let count = if let Ok(n) = word.parse::<usize>() {
+            if field {
+                Count::Field(Member::Unnamed(Index {
+                    index: u32::try_from(n).ok()?,
+                    span,
+                }))
+            } else {
+                Count::Literal(n)
+            }
+        } else if field {
+            let mut ident = syn::parse_str::<Ident>(word).ok()?;
+            ident.set_span(span);
+            Count::Field(Member::Named(ident))
+        } else {
+            // Not a count but the format type, such as the `x` in `{id:x}`.
+            return None;
+        };
+        *rest = &after[usize::from(field)..];
+        Some(count)
+    }
+}
+
+/// The format types `std::fmt` knows.
+const TYPES: [&str; 11] = ["", "?", "x?", "X?", "x", "X", "o", "b", "e", "E", "p"];
+
+impl Spec {
+    fn parse(spec: &str, span: Span) -> Result<Self, String> {
+        let mut rest = spec;
+        let is_align = |c: char| matches!(c, '<' | '^' | '>');
+        let mut chars = rest.chars();
+        let (fill, align) = match (chars.next(), chars.next()) {
+            (Some(fill), Some(align)) if is_align(align) => {
+                rest = chars.as_str();
+                (Some(fill), Some(align))
+            }
+            (Some(align), _) if is_align(align) => {
+                rest = &rest[1..];
+                (None, Some(align))
+            }
+            _ => (None, None),
+        };
+        let sign = rest.chars().next().filter(|c| matches!(c, '+' | '-'));
+        if sign.is_some() {
+            rest = &rest[1..];
+        }
+        let alternate = rest.starts_with('#');
+        if alternate {
+            rest = &rest[1..];
+        }
+        // `0$` is a width read from field `0`, not the `0` flag.
+        let zero = rest.starts_with('0') && !rest[1..].starts_with('$');
+        if zero {
+            rest = &rest[1..];
+        }
+        let width = Count::parse(&mut rest, span);
+        let precision = match rest.strip_prefix('.') {
+            Some(after) => {
+                rest = after;
+                if rest.starts_with('*') {
+                    return Err(
+                        "`.*` is not supported: read the precision from a field instead, e.g. `.digits$`".to_string(),
+                    );
+                }
+                match Count::parse(&mut rest, span) {
+                    Some(count) => Some(count),
+                    None => return Err("expected a precision after `.`".to_string()),
+                }
+            }
+            None => None,
+        };
+        let Some(ty) = TYPES.into_iter().find(|&ty| ty == rest) else {
+            return Err(format!(
+                "unknown format type `{rest}`, expected one of `?`, `x?`, `X?`, `x`, `X`, `o`, `b`, `e`, `E` or `p`"
+            ));
+        };
+
+        Ok(Self {
+            fill,
+            align,
+            sign,
+            alternate,
+            zero,
+            width,
+            precision,
+            ty,
+        })
+    }
+
+    /// The spec as written in a format string, with widths and precisions
+    /// read from fields referring to the arguments `width` and `precision`.
+    pub fn format_string(&self, width: &str, precision: &str) -> String {
+        let mut spec = String::new();
+        spec.extend(self.fill);
+        spec.extend(self.align);
+        spec.extend(self.sign);
+        if self.alternate {
+            spec.push('#');
+        }
+        if self.zero {
+            spec.push('0');
+        }
+        let push_count = |spec: &mut String, count: &Count, name: &str| match count {
+            Count::Literal(n) => spec.push_str(&n.to_string()),
+            Count::Field(_) => {
+                spec.push_str(name);
+                spec.push('$');
+            }
+        };
+        if let Some(count) = &self.width {
+            push_count(&mut spec, count, width);
+        }
+        if let Some(count) = &self.precision {
+            spec.push('.');
+            push_count(&mut spec, count, precision);
+        }
+        spec.push_str(self.ty);
+        spec
+    }
+
+    /// The fields the width and precision are read from, if any.
+    pub fn count_fields(&self) -> (Option<&Member>, Option<&Member>) {
+        fn field(count: &Option<Count>) -> Option<&Member> {
+            match count {
+                Some(Count::Field(member)) => Some(member),
+                _ => None,
+            }
+        }
+        (field(&self.width), field(&self.precision))
+    }
+
+    /// The `core::fmt` trait the spec formats with.
+    fn format_trait(&self) -> &'static str {
+        match self.ty {
+            "?" | "x?" | "X?" => "Debug",
+            "x" => "LowerHex",
+            "X" => "UpperHex",
+            "o" => "Octal",
+            "b" => "Binary",
+            "e" => "LowerExp",
+            "E" => "UpperExp",
+            "p" => "Pointer",
+            _ => "Display",
+        }
+    }
+}
+
+impl Argument {
+    /// Parses a field, position or path such as `address.city`.
+    pub(crate) fn parse_field(arg: &str, span: Span) -> Option<Self> {
+        let mut members = arg.split('.').map(str::trim).peekable();
+        // `self.` is allowed as a reminder that paths start at the type itself.
+        members.next_if(|&first| first == "self");
+        let mut members = members.map(|member| {
+            if let Ok(index) = member.parse::<u32>() {
+                Some(Member::Unnamed(Index { index, span }))
+            } else {
+                // Spanned so that rustc reports unknown nested fields
+                // against the template.
+                let mut ident = syn::parse_str::<Ident>(member).ok()?;
+                ident.set_span(span);
+                Some(Member::Named(ident))
+            }
+        });
+        let member = members.next()??;
+        let path = members.collect::<Option<_>>()?;
+        Some(Self::Field { member, path })
+    }
+
+    /// Parses any other expression, rejecting the ones that could change
+    /// state or leave the generated method early.
+    fn parse_expr(arg: &str, span: Span) -> syn::Result<Box<Expr>> {
+        let tokens = arg.parse::<TokenStream>()?;
+        let expr = syn::parse2::<Expr>(respan(tokens, span))?;
+        check_side_effects(&expr, span)?;
+        Ok(Box::new(expr))
+    }
+}
+
+/// Rejects the expressions that could change state or leave the generated
+/// method early.
+fn check_side_effects(expr: &Expr, span: Span) -> syn::Result<()> {
+    let mut check = SideEffects(None);
+    check.visit_expr(expr);
+    match check.0 {
+        Some(what) => Err(syn::Error::new(
+            span,
+            format!("{what} are not allowed in templates"),
+        )),
+        None => Ok(()),
+    }
+}
+
+/// The field and path an expression such as `address.lines`, `self.tags` or
+/// `0` reads, if it is nothing more than that.
+fn field_path(expr: &Expr) -> Option<(Member, Vec<Member>)> {
+    match expr {
+        Expr::Field(field) => {
+            if plain_name(&field.base).is_some_and(|base| base == "self") {
+                return Some((field.member.clone(), Vec::new()));
+            }
+            let (member, mut path) = field_path(&field.base)?;
+            path.push(field.member.clone());
+            Some((member, path))
+        }
+        Expr::Lit(lit) => match &lit.lit {
+            Lit::Int(index) => Some((
+                Member::Unnamed(Index {
+                    index: index.base10_parse().ok()?,
+                    span: index.span(),
+                }),
+                Vec::new(),
+            )),
+            _ => None,
+        },
+        _ => plain_name(expr).map(|name| (Member::Named(name.clone()), Vec::new())),
+    }
+}
+
+/// The identifier of a path consisting of a single plain name.
+pub fn plain_name(expr: &Expr) -> Option<&Ident> {
+    match expr {
+        Expr::Path(path) if path.qself.is_none() => path.path.get_ident(),
+        _ => None,
+    }
+}
+
+/// Finds the first `needle` in `s` outside of brackets and string literals.
+/// A `:` that is part of `::` and a `|` that is part of `||` do not count.
+fn find_top_level(s: &str, needle: char) -> Option<usize> {
+    let mut depth = 0usize;
+    let mut in_str = false;
+    let mut chars = s.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '\\' if in_str => {
+                chars.next();
+            }
+            '"' => in_str = !in_str,
+            _ if in_str => {}
+            '(' | '[' => depth += 1,
+            ')' | ']' => depth = depth.saturating_sub(1),
+            ':' | '|' if c == needle && chars.peek().map(|&(_, next)| next) == Some(c) => {
+                chars.next();
+            }
+            c if c == needle && depth == 0 => return Some(i),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Finds the `}` ending the tag `s` starts, outside of string literals, so
+/// that e.g. `{nick|"}"}` is a single placeholder.
+fn closing_brace(s: &str) -> Option<usize> {
+    let mut in_str = false;
+    let mut chars = s.char_indices();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '\\' if in_str => {
+                chars.next();
+            }
+            '"' => in_str = !in_str,
+            '}' if !in_str => return Some(i),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Gives every token in `tokens` the span `span`.
+pub fn respan(tokens: TokenStream, span: Span) -> TokenStream {
+    tokens
+        .into_iter()
+        .map(|mut tree| {
+            if let TokenTree::Group(group) = &tree {
+                let mut respanned = Group::new(group.delimiter(), respan(group.stream(), span));
+                respanned.set_span(span);
+                tree = TokenTree::Group(respanned);
+            }
+            tree.set_span(span);
+            tree
+        })
+        .collect()
+}
+
+/// Finds the first construct that placeholders must not contain.
+struct SideEffects(Option<&'static str>);
+
+impl<'ast> Visit<'ast> for SideEffects {
+    fn visit_expr(&mut self, expr: &'ast Expr) {
+        let what = match expr {
+            Expr::Assign(_) => Some("assignments"),
+            Expr::Binary(binary) if is_compound_assignment(&binary.op) => {
+                Some("compound assignments")
+            }
+            Expr::Return(_) | Expr::Break(_) | Expr::Continue(_) | Expr::Yield(_) => {
+                Some("control flow expressions")
+            }
+            Expr::Try(_) => Some("`?` operators"),
+            Expr::Await(_) | Expr::Async(_) => Some("async expressions"),
+            Expr::Loop(_) | Expr::While(_) | Expr::ForLoop(_) => Some("loops"),
+            Expr::Unsafe(_) => Some("unsafe blocks"),
+            Expr::Block(_) => Some("blocks"),
+            Expr::Macro(_) => Some("macros"),
+            Expr::Reference(reference) if reference.mutability.is_some() => Some("`&mut` borrows"),
+            _ => None,
+        };
+        if self.0.is_none() {
+            self.0 = what;
+        }
+        visit::visit_expr(self, expr);
+    }
+}
+
+fn is_compound_assignment(op: &BinOp) -> bool {
+    matches!(
+        op,
+        BinOp::AddAssign(_)
+            | BinOp::SubAssign(_)
+            | BinOp::MulAssign(_)
+            | BinOp::DivAssign(_)
+            | BinOp::RemAssign(_)
+            | BinOp::BitXorAssign(_)
+            | BinOp::BitAndAssign(_)
+            | BinOp::BitOrAssign(_)
+            | BinOp::ShlAssign(_)
+            | BinOp::ShrAssign(_)
+    )
+}
+
+/// How templates refer to a field: its name without any `r#` prefix, or its
+/// position.
+pub fn member_name(member: &Member) -> String {
+    match member {
+        Member::Named(ident) => ident.unraw().to_string(),
+        Member::Unnamed(index) => index.index.to_string(),
+    }
+}
+
+/// Turns a type or variant name such as `GoodMorning` into `Good morning`,
+/// the greeting of unit structs and variants without a template.
+pub fn humanize(name: &str) -> String {
+    let mut words = String::new();
+    for (i, c) in name.chars().enumerate() {
+        if c == '_' {
+            words.push(' ');
+        } else if i > 0 && c.is_uppercase() {
+            words.push(' ');
+            words.extend(c.to_lowercase());
+        } else {
+            words.push(c);
+        }
+    }
+    words
+}
+
+/// Maps byte ranges of a template back to spans inside its string literal.
+///
+/// Sub-spans are only available on nightly compilers; elsewhere, and for
+/// literals containing escapes, every range maps to the whole literal.
+pub(crate) struct SpanMap<'a> {
+    lit: &'a LitStr,
+    /// Byte offset of the template's first character in the literal's source.
+    offset: Option<usize>,
+}
+
+impl<'a> SpanMap<'a> {
+    pub(crate) fn new(lit: &'a LitStr, value: &str) -> Self {
+        let repr = lit.token().to_string();
+        let (prefix, suffix) = match repr.strip_prefix('r') {
+            Some(raw) => {
+                let hashes = raw.len() - raw.trim_start_matches('#').len();
+                (hashes + 2, hashes + 1)
+            }
+            None => (1, 1),
+        };
+        let offset = repr
+            .get(prefix..repr.len().saturating_sub(suffix))
+            .filter(|source| *source == value)
+            .map(|_| prefix);
+        Self { lit, offset }
+    }
+
+    pub(crate) fn span(&self, range: Range<usize>) -> Span {
+        self.offset
+            .and_then(|offset| {
+                self.lit
+                    .token()
+                    .subspan(offset + range.start..offset + range.end)
+            })
+            .unwrap_or_else(|| self.lit.span())
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use quote::ToTokens;
+
+    /// Parses `src` in `syntax`, returning the template written back with
+    /// square brackets, or the errors.
+    pub(crate) fn parse(src: &str, syntax: Syntax) -> Result<String, Vec<String>> {
+        let lit = LitStr::new(src, Span::call_site());
+        let mut errors = Error::accumulator();
+        let template = Template::parse(&lit, syntax, &mut errors);
+        match errors.finish() {
+            Ok(()) => Ok(render(&template.segments)),
+            Err(errors) => Err(errors.into_iter().map(|e| e.to_string()).collect()),
+        }
+    }
+
+    fn ok(src: &str) -> String {
+        parse(src, Syntax::Default).unwrap_or_else(|errors| panic!("{src}: {errors:?}"))
+    }
+
+    fn err(src: &str) -> Vec<String> {
+        parse(src, Syntax::Default).expect_err(src)
+    }
+
+    /// Writes `segments` in a syntax of square brackets that shows how they
+    /// were understood, e.g. `Hi [name:>8]` for `Hi {name:>8}`.
+    pub(crate) fn render(segments: &[Segment]) -> String {
+        segments.iter().map(render_segment).collect()
+    }
+
+    fn render_segment(segment: &Segment) -> String {
+        match segment {
+            Segment::Text(text) => text.clone(),
+            Segment::Placeholder(p) => {
+                let mut s = format!("[{}", render_arg(&p.arg));
+                if let Some(spec) = &p.spec {
+                    s += &format!(":{}", spec.format_string("w", "p"));
+                }
+                for filter in &p.filters {
+                    s += &format!("|{}", filter.name);
+                    let args = filter
+                        .args
+                        .iter()
+                        .map(|arg| arg.to_token_stream().to_string());
+                    let named = filter
+                        .named
+                        .iter()
+                        .map(|(name, arg)| format!("{name}={}", arg.to_token_stream()));
+                    let args = args.chain(named).collect::<Vec<_>>();
+                    if !args.is_empty() {
+                        s += &format!("({})", args.join(","));
+                    }
+                }
+                if let Some(fallback) = &p.fallback {
+                    s += &format!("|{fallback:?}");
+                }
+                s + "]"
+            }
+            Segment::IfSome { member, body, .. } => {
+                let name = member_name(member);
+                format!("[?{name}]{}[/{name}]", render(body))
+            }
+            Segment::If {
+                cond,
+                then,
+                otherwise,
+                ..
+            } => format!(
+                "[if {}]{}[else]{}[/if]",
+                cond.to_token_stream(),
+                render(then),
+                render(otherwise)
+            ),
+            Segment::Each { each, body, .. } => {
+                let mut s = format!("[each {} in {}", each.item, render_arg(&each.collection));
+                if let Some(sep) = &each.sep {
+                    s += &format!(" sep={sep:?}");
+                }
+                if let Some(last) = &each.last {
+                    s += &format!(" last={last:?}");
+                }
+                format!("{s}]{}[/each]", render(body))
+            }
+            Segment::Plural {
+                count,
+                offset,
+                exact,
+                forms,
+                other,
+                ..
+            } => {
+                let mut s = format!("[plural {}", render_arg(count));
+                if *offset != 0 {
+                    s += &format!(" offset={offset}");
+                }
+                for (n, message) in exact {
+                    s += &format!(" ={n}({})", render(message));
+                }
+                for (category, message) in forms {
+                    s += &format!(" {category}({})", render(message));
+                }
+                format!("{s} other({})]", render(other))
+            }
+            Segment::PluralCount(_) => "#".to_string(),
+            Segment::Select {
+                value,
+                cases,
+                other,
+                ..
+            } => {
+                let mut s = format!("[select {}", render_arg(value));
+                for (case, message) in cases {
+                    s += &format!(" {case}({})", render(message));
+                }
+                format!("{s} other({})]", render(other))
+            }
+        }
+    }
+
+    fn render_arg(arg: &Argument) -> String {
+        match arg {
+            Argument::Field { member, path } => std::iter::once(member)
+                .chain(path)
+                .map(member_name)
+                .collect::<Vec<_>>()
+                .join("."),
+            Argument::Expr(expr) => format!("({})", expr.to_token_stream()),
+        }
+    }
+
+    #[test]
+    fn text_and_placeholders() {
+        assert_eq!(ok("Hi, I am {name}."), "Hi, I am [name].");
+        assert_eq!(ok("{0} and {1}"), "[0] and [1]");
+        assert_eq!(ok("{{name}} is {{ and }}"), "{name} is { and }");
+        assert_eq!(ok("{ name }"), "[name]");
+        assert_eq!(
+            ok("{address.city} {self.address.city}"),
+            "[address.city] [address.city]"
+        );
+        assert_eq!(ok("{r#type}"), "[type]");
+    }
+
+    #[test]
+    fn expressions() {
+        assert_eq!(ok("{age + 1}"), "[(age + 1)]");
+        assert_eq!(ok("{full_name()}"), "[(full_name ())]");
+        assert_eq!(ok("{tags.len():>4}"), "[(tags . len ()):>4]");
+        assert_eq!(
+            ok("{tags.iter().map(|t| t.len()).sum::<usize>()}"),
+            "[(tags . iter () . map (| t | t . len ()) . sum :: < usize > ())]"
+        );
+        assert_eq!(ok(r#"{tags.join("}")}"#), r#"[(tags . join ("}"))]"#);
+    }
+
+    #[test]
+    fn rejected_expressions() {
+        for (src, what) in [
+            ("{age = 1}", "assignments"),
+            ("{age += 1}", "compound assignments"),
+            ("{age?}", "`?` operators"),
+            ("{return}", "control flow expressions"),
+            ("{f(|| break)}", "control flow expressions"),
+            ("{x.await}", "async expressions"),
+            ("{println!(\"hi\")}", "macros"),
+            ("{(&mut tags).len()}", "`&mut` borrows"),
+            ("{tags.iter().map(|t| format!(\"{t}\"))}", "macros"),
+        ] {
+            let errors = err(src);
+            assert!(
+                errors[0].ends_with(&format!("{what} are not allowed in templates")),
+                "{src}: {errors:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn unbalanced_braces() {
+        assert_eq!(err("Hi {name"), ["unterminated placeholder: expected `}`"]);
+        assert_eq!(
+            err("Hi name}"),
+            ["unmatched `}` in template: use `}}` for a literal brace"]
+        );
+        assert_eq!(
+            err("{}"),
+            ["placeholder `{}` has no argument to format: name a field, e.g. `{name}`"]
+        );
+        // Every mistake is reported, not only the first
+        assert_eq!(err("{} } {x").len(), 3);
+    }
+
+    #[test]
+    fn format_specs() {
+        for (src, spec) in [
+            ("{x:>8}", ">8"),
+            ("{x:*^8}", "*^8"),
+            ("{x:<}", "<"),
+            ("{x:+.2}", "+.2"),
+            ("{x:#x}", "#x"),
+            ("{x:#?}", "#?"),
+            ("{x:08.3e}", "08.3e"),
+            ("{x:x?}", "x?"),
+            ("{x:>width$}", ">w$"),
+            ("{x:.digits$}", ".p$"),
+            ("{x:0$}", "w$"),
+            ("{x:>1$.2$}", ">w$.p$"),
+            ("{x:}", ""),
+        ] {
+            assert_eq!(ok(src), format!("[x:{spec}]"), "{src}");
+        }
+    }
+
+    #[test]
+    fn format_spec_fields() {
+        let spec = Spec::parse("*^width$.1$X", Span::call_site()).unwrap();
+        assert_eq!((spec.fill, spec.align), (Some('*'), Some('^')));
+        let (width, precision) = spec.count_fields();
+        assert_eq!(width.map(member_name).as_deref(), Some("width"));
+        assert_eq!(precision.map(member_name).as_deref(), Some("1"));
+        assert_eq!(spec.format_trait(), "UpperHex");
+
+        let spec = Spec::parse("0$", Span::call_site()).unwrap();
+        assert!(!spec.zero);
+        assert_eq!(spec.count_fields().0.map(member_name).as_deref(), Some("0"));
+        let spec = Spec::parse("05", Span::call_site()).unwrap();
+        assert!(spec.zero);
+        assert!(matches!(spec.width, Some(Count::Literal(5))));
+    }
+
+    #[test]
+    fn invalid_format_specs() {
+        for (spec, error) in [
+            (".*", "`.*` is not supported"),
+            (".", "expected a precision after `.`"),
+            ("y", "unknown format type `y`"),
+            (">8q", "unknown format type `q`"),
+        ] {
+            let e = Spec::parse(spec, Span::call_site()).unwrap_err();
+            assert!(e.starts_with(error), "{spec}: {e}");
+        }
+        assert!(err("{x:y}")[0].starts_with("invalid format spec in `{x:y}`"));
+    }
+
+    #[test]
+    fn top_level_separators() {
+        assert_eq!(find_top_level("a:b", ':'), Some(1));
+        assert_eq!(find_top_level("a::b:c", ':'), Some(4));
+        assert_eq!(find_top_level("f(a:b):c", ':'), Some(6));
+        assert_eq!(find_top_level("x[a|b]|y", '|'), Some(6));
+        assert_eq!(find_top_level("a || b|c", '|'), Some(6));
+        assert_eq!(find_top_level(r#""a|b"|c"#, '|'), Some(5));
+        assert_eq!(find_top_level(r#""a\"|b"|c"#, '|'), Some(7));
+        assert_eq!(find_top_level("abc", ':'), None);
+        assert_eq!(closing_brace(r#"{x|"}"}"#), Some(6));
+        assert_eq!(closing_brace(r#"{x|"\"}"}"#), Some(8));
+        assert_eq!(closing_brace(r#"{x|"}"#), None);
+    }
+
+    #[test]
+    fn optional_fields() {
+        assert_eq!(ok(r#"{nick|"friend"}"#), r#"[nick|"friend"]"#);
+        assert_eq!(ok(r#"{nick|"}"}"#), r#"[nick|"}"]"#);
+        assert_eq!(ok(r#"{nick:>8|"-"}"#), r#"[nick:>8|"-"]"#);
+        assert_eq!(ok("{?nick}aka {nick}{/nick}"), "[?nick]aka [nick][/nick]");
+        assert_eq!(ok("{?0}#{0}{/0}"), "[?0]#[0][/0]");
+        assert_eq!(
+            err(r#"{nick|"a"|upper}"#),
+            [r#"the fallback `"a"` in `{nick|"a"|upper}` must come last"#]
+        );
+        assert!(err(r#"{nick|"friend}"#)[0].starts_with("unterminated placeholder"));
+        assert!(err(r#"{nick|"a" "b"}"#)[0].starts_with("invalid fallback"));
+        assert!(err("{?nick.name}{/nick.name}")[0].starts_with("invalid section `{?nick.name}`"));
+    }
+
+    #[test]
+    fn conditionals() {
+        assert_eq!(
+            ok("{if age > 18}adult{/if}"),
+            "[if age > 18]adult[else][/if]"
+        );
+        assert_eq!(
+            ok("{if vip}Dear {name}{else}Hi{/if}!"),
+            "[if vip]Dear [name][else]Hi[/if]!"
+        );
+        assert_eq!(
+            ok("{if a}{if b}ab{else}a{/if}{/if}"),
+            "[if a][if b]ab[else]a[/if][else][/if]"
+        );
+        assert_eq!(ok("{ifs}"), "[ifs]");
+        assert_eq!(
+            err("{if a}x{else}y{else}z{/if}"),
+            ["duplicate `{else}`: an `{if}` has at most one"]
+        );
+        assert_eq!(
+            err("x{else}y"),
+            ["unexpected `{else}`: it must be inside an `{if ...}` section"]
+        );
+        assert_eq!(err("{if a}x"), ["unclosed section: expected `{/if}`"]);
+        assert_eq!(
+            err("{?nick}x{/if}{/nick}"),
+            ["unexpected `{/if}`: expected `{/nick}`"]
+        );
+        assert_eq!(
+            err("x{/if}"),
+            ["unexpected `{/if}`: there is no open section to close"]
+        );
+        assert!(err("{if a = 1}x{/if}")[0].starts_with("invalid condition in `{if a = 1}`"));
+        // A malformed opening tag still opens its section
+        assert_eq!(err("{if +}x{else}y{/if}").len(), 1);
+    }
+
+    #[test]
+    fn loops() {
+        assert_eq!(
+            ok("{#each tags}#{it} {/each}"),
+            "[each it in tags]#[it] [/each]"
+        );
+        assert_eq!(
+            ok(r#"{#each tag in tags sep=", " last=" and "}{tag}{/each}"#),
+            r#"[each tag in tags sep=", " last=" and "][tag][/each]"#
+        );
+        assert_eq!(
+            ok("{#each tags list}{it}{/each}"),
+            r#"[each it in tags sep=", " last=" and "][it][/each]"#
+        );
+        assert_eq!(
+            ok(r#"{#each tags list sep="; "}{it}{/each}"#),
+            r#"[each it in tags sep="; " last=" and "][it][/each]"#
+        );
+        assert_eq!(
+            ok(r#"{#each scores.iter().rev() sep="}"}{it}{/each}"#),
+            r#"[each it in (scores . iter () . rev ()) sep="}"][it][/each]"#
+        );
+        assert_eq!(
+            ok("{#each m in members}{#each m.tags}{it}{/each}{/each}"),
+            "[each m in members][each it in m.tags][it][/each][/each]"
+        );
+        assert!(err("{#each tags by=\",\"}{/each}")[0].contains("unknown option `by`"));
+        assert!(err(r#"{#each tags sep="," sep=";"}{/each}"#)[0].contains("duplicate option `sep`"));
+        assert!(err("{#each}{/each}")[0].starts_with("invalid section `{#each}`"));
+        assert_eq!(
+            err("{#each tags}x"),
+            ["unclosed section: expected `{/each}`"]
+        );
+    }
+
+    #[test]
+    fn filters() {
+        assert_eq!(ok("{name|trim|upper}"), "[name|trim|upper]");
+        assert_eq!(ok("{bio|truncate:40}"), "[bio|truncate(40)]");
+        assert_eq!(
+            ok(r#"{n|plural:"cat","cats"}"#),
+            r#"[n|plural("cat","cats")]"#
+        );
+        assert_eq!(
+            ok(r#"{n|plural(one = "kot", few = "koty")}"#),
+            r#"[n|plural(one="kot",few="koty")]"#
+        );
+        assert_eq!(ok(r#"{n|upper|"none"}"#), r#"[n|upper|"none"]"#);
+        assert_eq!(ok("{a || b}"), "[(a || b)]");
+        assert!(err("{name|truncate:}")[0].starts_with("invalid filter `truncate:`"));
+    }
+
+    #[test]
+    fn syntax_option() {
+        let lit = |s: &str| LitStr::new(s, Span::call_site());
+        assert_eq!(Syntax::from_lit(&lit("icu")).unwrap(), Syntax::Icu);
+        assert_eq!(Syntax::from_lit(&lit("default")).unwrap(), Syntax::Default);
+        assert!(Syntax::from_lit(&lit("jinja")).is_err());
+    }
+}